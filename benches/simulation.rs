@@ -0,0 +1,158 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use brains::{
+    andis::{AndiN, AndiS, AndiScratch},
+    board::{Board, OccupancyGrid},
+    creature::{Creatures, SpawnPattern},
+    genes::{crossover_parallel, select_top_k, Genom},
+    world::{World, WorldBuilder},
+};
+
+/// every bench starts from the same world, so the numbers are comparable across runs
+const SEED: u64 = 42;
+
+/// a seeded world of `creatures` creatures, its generations last `steps_in_generation` steps
+fn world(creatures: usize, steps_in_generation: u32) -> World<StdRng, AndiN> {
+    WorldBuilder::new()
+        .creatures(creatures)
+        .genome_length(16)
+        .steps_in_generation(steps_in_generation)
+        .seed(SEED)
+        .build()
+}
+
+fn simulate(c: &mut Criterion) {
+    let mut group = c.benchmark_group("simulate");
+    for creatures in [1_000, 10_000] {
+        let mut world = world(creatures, u32::MAX);
+        group.bench_with_input(BenchmarkId::from_parameter(creatures), &creatures, |b, _| {
+            b.iter(|| world.simulate())
+        });
+    }
+    group.finish();
+}
+
+fn decide(c: &mut Criterion) {
+    let mut group = c.benchmark_group("decide");
+    let mut rng = StdRng::seed_from_u64(SEED);
+    let mut creatures: Creatures<AndiN> =
+        Creatures::new(1_000, 16, 0, 1_000, 128, 128, SpawnPattern::default(), &mut rng);
+    let mut board = Board::new(128, 128);
+    board.fill(&creatures.positions);
+
+    // fresh buffers every step against the ones kept from step to step
+    group.bench_function("allocating", |b| {
+        b.iter(|| AndiN::decide_actions(&creatures, &board, 0))
+    });
+    let mut scratch = AndiScratch::default();
+    let mut rngs = std::mem::take(&mut creatures.rngs);
+    group.bench_function("reusing", |b| {
+        b.iter(|| AndiN::decide_actions_into(&creatures, &mut rngs, &board, 0, &mut scratch))
+    });
+    group.finish();
+}
+
+fn crossover(c: &mut Criterion) {
+    let mut group = c.benchmark_group("crossover");
+    let mut rng = StdRng::seed_from_u64(SEED);
+    let a: Genom<AndiN, AndiS> = Genom::random(64, &mut rng);
+    let b: Genom<AndiN, AndiS> = Genom::random(64, &mut rng);
+
+    group.bench_function("crossover", |bench| bench.iter(|| Genom::crossover(&a, &b, &mut rng)));
+    let mut child = Genom::with_capacity(64);
+    group.bench_function("crossover_mut", |bench| {
+        bench.iter(|| Genom::crossover_mut(&a, &b, &mut child, &mut rng))
+    });
+    group.finish();
+}
+
+fn end_generation(c: &mut Criterion) {
+    let mut group = c.benchmark_group("end_generation");
+    // one step per generation, so the generations are dominated by selecting and breeding
+    let mut world = world(1_000, 1);
+    group.bench_function("1000 creatures", |b| b.iter(|| world.simulate()));
+    group.finish();
+}
+
+fn breeding(c: &mut Criterion) {
+    let mut group = c.benchmark_group("breeding");
+    let mut rng = StdRng::seed_from_u64(SEED);
+    let parents: Vec<Genom<AndiN, AndiS>> =
+        (0..1_000).map(|_| Genom::random(16, &mut rng)).collect();
+    // a large population, 100k children bred from random pairs of the parents
+    let pairs: Vec<_> = (0..100_000)
+        .map(|_| {
+            let (a, b) = (rng.gen_range(0..parents.len()), rng.gen_range(0..parents.len()));
+            (&parents[a], &parents[b])
+        })
+        .collect();
+
+    // at least two threads, so there is always something to compare to
+    let cores = std::thread::available_parallelism().map_or(2, |cores| cores.get().max(2));
+    for threads in [1, cores] {
+        group.bench_with_input(BenchmarkId::from_parameter(threads), &threads, |b, &threads| {
+            b.iter(|| crossover_parallel(&pairs, threads, &mut rng))
+        });
+    }
+    group.finish();
+}
+
+fn top_k(c: &mut Criterion) {
+    let mut group = c.benchmark_group("top_k");
+    let mut rng = StdRng::seed_from_u64(SEED);
+    let scores: Vec<f32> = (0..100_000).map(|_| rng.gen()).collect();
+    let k = 100;
+
+    group.bench_function("heap", |b| b.iter(|| select_top_k(&scores, k)));
+    group.bench_function("sort", |b| {
+        b.iter(|| {
+            let mut indices: Vec<_> = (0..scores.len()).collect();
+            indices.sort_by(|&i, &j| scores[j].total_cmp(&scores[i]));
+            indices.truncate(k);
+            indices
+        })
+    });
+    group.finish();
+}
+
+fn occupancy(c: &mut Criterion) {
+    let mut group = c.benchmark_group("occupancy");
+    let mut rng = StdRng::seed_from_u64(SEED);
+    let (width, height) = (512, 512);
+    let positions: Vec<(i32, i32)> =
+        (0..1_000).map(|_| (rng.gen_range(0..width), rng.gen_range(0..height))).collect();
+
+    // clearing and filling the grid, as every step does
+    let mut bits = OccupancyGrid::new(width, height);
+    group.bench_function("bitset", |b| {
+        b.iter(|| {
+            bits.clear_all();
+            for &(x, y) in &positions {
+                bits.set(x, y);
+            }
+        })
+    });
+    let mut indices = vec![usize::MAX; (width * height) as usize];
+    group.bench_function("indices", |b| {
+        b.iter(|| {
+            indices.fill(usize::MAX);
+            for (i, &(x, y)) in positions.iter().enumerate() {
+                indices[(x + width * y) as usize] = i;
+            }
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    simulate,
+    decide,
+    crossover,
+    end_generation,
+    breeding,
+    top_k,
+    occupancy
+);
+criterion_main!(benches);
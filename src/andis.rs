@@ -1,35 +1,108 @@
 use crate::{
-    creature::{Creatures, NeuronNucl},
-    genes::{Genom, Nucl, Scorer},
+    board::{Board, BoundaryMode},
+    brain::{Neuron, Synapse},
+    creature::{Arbitration, Creatures, NeuronNucl, Point2},
+    genes::{Genom, Nucl, Scorer, Simu},
+    spatial::SpatialGrid,
+    zone::{SelectionZone, Zone},
 };
 use num_derive::FromPrimitive;
 use num_traits::FromPrimitive;
-use rand::Rng;
-use std::fmt::{Debug, Display};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::{
+    error::Error,
+    fmt::{Debug, Display},
+    time::Instant,
+};
+
+/// number of nucleotides per line of [`Genom::to_hex_string`]
+const HEX_PER_LINE: usize = 8;
+
+/// frequency resolution of the oscillator input
+const FREQ_STEP: f32 = std::f32::consts::PI / 64.0;
+
+/// number of cells the `WcF` input looks ahead
+const WCF_RANGE: i32 = 8;
+
+/// amount of pheromone a creature leaves behind when its `Deposit` neuron fires
+const PHEROMONE_DEPOSIT: f32 = 0.5;
+
+/// the fastest a creature can get, in cells per move
+const MAX_SPEED: i32 = 3;
+
+/// synapse weights lie in `[-MAX_WEIGHT, MAX_WEIGHT]`, negative weights inhibit
+const MAX_WEIGHT: f32 = 4.0;
+
+/// accumulated neuron inputs saturate at `±MAX_POTENTIAL` before the activation function
+const MAX_POTENTIAL: f32 = 1.0e6;
+
+// bit layout of an `AndiN`, see there
+const FREQ_SHIFT: u32 = 28;
+const FREQ_MASK: u32 = 0xF;
+const INPUT_SHIFT: u32 = 24;
+const INPUT_MASK: u32 = 0xF;
+const WEIGHT_SHIFT: u32 = 8;
+const WEIGHT_MASK: u32 = 0xFFFF;
+const SINK_INTERNAL: u32 = 1 << 7;
+const SOURCE_INTERNAL: u32 = 1 << 6;
+const OUTPUT_SHIFT: u32 = 0;
+const OUTPUT_MASK: u32 = 0x3F;
+
+/// number of internal neurons [`AndiN`]'s `Display` tells apart, as many as the output field holds
+const DISPLAYED_INTERNAL: usize = OUTPUT_MASK as usize + 1;
 
 // -------------------------------------------------------------------------------------------------
 // --- Andis Nucleotides ---------------------------------------------------------------------------
 // -------------------------------------------------------------------------------------------------
-#[derive(Default, Clone)]
+
+/// A nucleotide encoding one synapse in 32 bits, none of the fields overlap:
+///
+/// | bits  | field                                   |
+/// |-------|-----------------------------------------|
+/// | 28-31 | oscillator frequency (see `frequency`)  |
+/// | 24-27 | input neuron                            |
+/// |  8-23 | weight                                  |
+/// |  7    | the destination is an internal neuron   |
+/// |  6    | the source is an internal neuron        |
+/// |  0-5  | output neuron                           |
+///
+/// Input and output indices wrap around the number of neurons. If a brain has internal neurons,
+/// bits 6 and 7 turn the input and output index into the index of an internal neuron instead, see
+/// [`NeuronNucl::synapse`].
+#[derive(Default, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(transparent))]
 #[repr(C)]
 pub struct AndiN {
     encoded: u32,
 }
 
 impl Display for AndiN {
+    /// `source weight -> destination`, e.g. `Osc 0.75 -> MvE`. Internal neurons are written as
+    /// `I<index>`, e.g. `Osc 0.75 -> I2`, with the index as encoded: a brain with fewer internal
+    /// neurons wraps it around.
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "\x1b[32m{:?}\x1b[0m", self.input())?;
-        write!(f, " \x1b[90m{:.2}\x1b[0m", self.weight())?;
-        write!(f, " \x1b[33m{:?}\x1b[0m", self.output())
+        let synapse = self.synapse(DISPLAYED_INTERNAL);
+        match synapse.source {
+            Neuron::Internal(i) => write!(f, "\x1b[36mI{}\x1b[0m", i)?,
+            _ => write!(f, "\x1b[32m{:?}\x1b[0m", self.input())?,
+        }
+        write!(f, " \x1b[90m{:.2}\x1b[0m ->", synapse.weight)?;
+        match synapse.sink {
+            Neuron::Internal(i) => write!(f, " \x1b[36mI{}\x1b[0m", i),
+            _ => write!(f, " \x1b[33m{:?}\x1b[0m", self.output()),
+        }
     }
 }
 
 impl Debug for AndiN {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "\x1b[32m{:x}\x1b[0m", self.encoded >> 28)?;
-        write!(f, "{:2x}", (self.encoded >> 12) & 0xFFFF)?;
-        write!(f, "\x1b[90m{:02x}\x1b[0m", (self.encoded >> 4) & 0xFF)?;
-        write!(f, "\x1b[33m{:x}\x1b[0m", self.encoded & 0x0_0000_00F)
+        write!(f, "{:x}", self.field(FREQ_SHIFT, FREQ_MASK))?;
+        write!(f, "\x1b[32m{:x}\x1b[0m", self.field(INPUT_SHIFT, INPUT_MASK))?;
+        write!(f, "\x1b[90m{:04x}\x1b[0m", self.field(WEIGHT_SHIFT, WEIGHT_MASK))?;
+        let output_mask = OUTPUT_MASK | SINK_INTERNAL | SOURCE_INTERNAL;
+        write!(f, "\x1b[33m{:02x}\x1b[0m", self.field(OUTPUT_SHIFT, output_mask))
     }
 }
 
@@ -38,17 +111,351 @@ impl AndiN {
         Self { encoded }
     }
 
+    /// Packs the fields into a nucleotide, the inverse of [`AndiN::input`], [`AndiN::output`] and
+    /// [`AndiN::weight`]. The weight is clamped to `[-4, 4]` and quantised to 16 bits, the
+    /// oscillator frequency is left at `0`.
+    pub fn encode(input: InputNeurons, output: OutputNeurons, weight: f32) -> Self {
+        let weight = weight.clamp(-MAX_WEIGHT, MAX_WEIGHT);
+        let weight = ((weight + MAX_WEIGHT) / (2.0 * MAX_WEIGHT) * 65536.0)
+            .round()
+            .min(WEIGHT_MASK as f32) as u32;
+        let input: u32 = input.into();
+        let output: u32 = output.into();
+        Self {
+            encoded: input | (weight << WEIGHT_SHIFT) | output,
+        }
+    }
+
+    /// flips bit `bit` of the encoding, counted from the least significant one (`0..32`)
+    pub fn mutate_bit(&mut self, bit: u32) {
+        self.encoded ^= 1 << (bit % u32::BITS);
+    }
+
+    fn field(&self, shift: u32, mask: u32) -> u32 {
+        (self.encoded >> shift) & mask
+    }
+
     pub fn input(&self) -> InputNeurons {
         self.encoded.into()
     }
 
+    /// oscillator frequency in radians per step, decoded from the topmost nibble. A frequency of
+    /// `0.0` makes the oscillator a constant signal.
+    pub fn frequency(&self) -> f32 {
+        self.field(FREQ_SHIFT, FREQ_MASK) as f32 * FREQ_STEP
+    }
+
+    /// the synapse weight in `[-4, 4)`, negative weights inhibit the output
     pub fn weight(&self) -> f32 {
-        ((self.encoded >> 8) & 0xFFFF) as f32 / 65536.0
+        self.field(WEIGHT_SHIFT, WEIGHT_MASK) as f32 / 65536.0 * 2.0 * MAX_WEIGHT - MAX_WEIGHT
     }
 
     pub fn output(&self) -> OutputNeurons {
         self.encoded.into()
     }
+
+    /// Lets every creature's brain decide what to do at `step`, without changing anything. The
+    /// actions are ordered by creature.
+    pub fn decide_actions(creatures: &Creatures<AndiN>, board: &Board, step: u32) -> Vec<Action> {
+        let mut rngs = creatures.rngs.clone();
+        let mut scratch = AndiScratch::default();
+        AndiN::decide_actions_into(creatures, &mut rngs, board, step, &mut scratch);
+        scratch.actions
+    }
+
+    /// [`AndiN::decide_actions`] into the reused buffers of `scratch`. Each creature draws its
+    /// random numbers from its own generator in `rngs`, so the actions don't depend on the order
+    /// the creatures are evaluated in.
+    pub fn decide_actions_into(
+        creatures: &Creatures<AndiN>,
+        rngs: &mut [StdRng],
+        board: &Board,
+        step: u32,
+        scratch: &mut AndiScratch,
+    ) {
+        scratch.neighbors.rebuild(
+            &creatures.positions,
+            board.width,
+            board.height,
+            board.boundary == BoundaryMode::Wrap,
+        );
+        let surroundings = Surroundings {
+            creatures,
+            board,
+            neighbors: &scratch.neighbors,
+            step,
+        };
+
+        scratch.actions.clear();
+        scratch.activations.clear();
+        for (i, rng) in rngs.iter_mut().enumerate() {
+            surroundings.decide(i, rng, &mut scratch.neurons, &mut scratch.actions);
+            if creatures.watched == Some(i) {
+                scratch.activations.clone_from(&scratch.neurons);
+            }
+        }
+    }
+
+    /// Carries out the `actions` on the board. Each move goes [`Genom::speed`] cells one at a time,
+    /// off the board they are clamped or wrapped depending on the board's [`BoundaryMode`]. A move
+    /// stops in front of the first occupied cell on its way. Moving costs a creature one unit of
+    /// energy, once it has none left its moves are skipped.
+    ///
+    /// A predator moving onto a prey kills it and takes its cell, the killed creatures are removed
+    /// once all actions have been carried out.
+    pub fn apply_actions(creatures: &mut Creatures<AndiN>, actions: &[Action], board: &mut Board) {
+        let mut alive = vec![true; creatures.positions.len()];
+        for action in actions.iter() {
+            if !alive[action.creature()] {
+                continue;
+            }
+            let (i, dx, dy) = match action {
+                Action::MoveEast(i) => (*i, 1, 0),
+                Action::MoveWest(i) => (*i, -1, 0),
+                Action::MoveNorth(i) => (*i, 0, 1),
+                Action::MoveSouth(i) => (*i, 0, -1),
+                Action::MoveNE(i) => (*i, 1, 1),
+                Action::MoveNW(i) => (*i, -1, 1),
+                Action::MoveSE(i) => (*i, 1, -1),
+                Action::MoveSW(i) => (*i, -1, -1),
+                Action::MoveForward(i) => {
+                    let (dx, dy) = creatures.facing[*i].delta();
+                    (*i, dx, dy)
+                }
+                Action::TurnLeft(i) => {
+                    creatures.facing[*i] = creatures.facing[*i].left();
+                    continue;
+                }
+                Action::TurnRight(i) => {
+                    creatures.facing[*i] = creatures.facing[*i].right();
+                    continue;
+                }
+                Action::Deposit(i) => {
+                    board.deposit_pheromone(&creatures.positions[*i], PHEROMONE_DEPOSIT);
+                    continue;
+                }
+            };
+
+            if creatures.energy[i] <= 0 {
+                continue;
+            }
+            creatures.headings[i] = (dx, dy);
+            let start = (creatures.positions[i].x, creatures.positions[i].y);
+            for _ in 0..creatures.population.genoms[i].speed() {
+                let pos = &creatures.positions[i];
+                let target = board.resolve(pos.x + dx, pos.y + dy);
+                let prey = board
+                    .occupant(target.x, target.y)
+                    .filter(|&prey| creatures.is_predator(i) && !creatures.is_predator(prey));
+                if let Some(prey) = prey {
+                    alive[prey] = false;
+                    creatures.food_eaten[i] += 1;
+                    board.vacate(&target);
+                    board.move_occupant(i, pos, &target);
+                    creatures.positions[i] = target;
+                    break;
+                }
+                if !board.is_free(target.x, target.y) {
+                    break;
+                }
+                board.move_occupant(i, pos, &target);
+                creatures.positions[i] = target;
+            }
+            if (creatures.positions[i].x, creatures.positions[i].y) != start {
+                creatures.energy[i] -= 1;
+            }
+        }
+        creatures.retain(board, |i| alive[i]);
+    }
+}
+
+/// what the brains sense of the world in a step, the same for every creature
+pub struct Surroundings<'a> {
+    pub creatures: &'a Creatures<AndiN>,
+    pub board: &'a Board,
+    /// the positions of the creatures, for the senses of their neighbors
+    pub neighbors: &'a SpatialGrid,
+    pub step: u32,
+}
+
+impl Surroundings<'_> {
+    /// Lets creature `i` decide what to do, its actions are appended to `actions`. Stochastic
+    /// decisions draw from `rng`, `neurons` is a buffer for the brain.
+    pub fn decide(
+        &self,
+        i: usize,
+        rng: &mut StdRng,
+        neurons: &mut Vec<f32>,
+        actions: &mut Vec<Action>,
+    ) {
+        let creatures = self.creatures;
+        let board = self.board;
+        let width = board.width;
+        let genom = &creatures.population.genoms[i];
+        let pos = &creatures.positions[i];
+        let phase = creatures.phases[i];
+        let (dx, dy) = creatures.headings[i];
+
+        // setup temp brain: the outputs first, then the internal neurons
+        let n_outputs = OutputNeurons::COUNT as usize;
+        let n_internal = creatures.n_internal_neurons;
+        neurons.clear();
+        neurons.resize(n_outputs + n_internal, 0.0);
+        let neuron_index = |neuron: Neuron| match neuron {
+            Neuron::Output(o) => o,
+            Neuron::Internal(i) => n_outputs + i,
+            Neuron::Input(_) => unreachable!("no synapse ends in an input"),
+        };
+
+        let nearest = match self.neighbors.nearest(pos, creatures.sensor_range) {
+            Some((dx, dy)) => {
+                let distance = (dx as f32).hypot(dy as f32);
+                (dx as f32 / distance, dy as f32 / distance)
+            }
+            None => (0.0, 0.0),
+        };
+
+        // the internal neurons form a single layer: they take the signals of the inputs and pass
+        // their activation on to the outputs, synapses between two of them carry nothing
+        for nucl in genom.nucleotides.iter() {
+            let synapse = nucl.synapse(n_internal);
+            if !matches!(synapse.source, Neuron::Input(_)) {
+                continue;
+            }
+            let signal = match nucl.input() {
+                InputNeurons::Osc => (self.step as f32 * nucl.frequency() + phase).sin(),
+                InputNeurons::PL => {
+                    if pos.x < width / 2 {
+                        1.0
+                    } else {
+                        0.0
+                    }
+                }
+                InputNeurons::PR => {
+                    if pos.x >= width / 2 {
+                        1.0
+                    } else {
+                        0.0
+                    }
+                }
+                InputNeurons::WcF => board.wall_ahead(pos, dx, dy, WCF_RANGE),
+                InputNeurons::WallDist => board.wall_distance(pos),
+                InputNeurons::CrowdDensity => self.neighbors.crowd(pos, creatures.sensor_range),
+                InputNeurons::Pheromone => board.pheromone_around(pos, creatures.sensor_range),
+                InputNeurons::NearestDx => nearest.0,
+                InputNeurons::NearestDy => nearest.1,
+                _ => 0.0,
+            } * synapse.weight;
+
+            neurons[neuron_index(synapse.sink)] += signal;
+        }
+
+        let activate = |x: &mut f32| {
+            *x = creatures.activation.apply(saturate(*x));
+            debug_assert!(x.is_finite(), "neuron activation is not finite: {}", x);
+        };
+        neurons[n_outputs..].iter_mut().for_each(activate);
+        for nucl in genom.nucleotides.iter() {
+            let synapse = nucl.synapse(n_internal);
+            if let (Neuron::Internal(i), Neuron::Output(o)) = (synapse.source, synapse.sink) {
+                neurons[o] += neurons[n_outputs + i] * synapse.weight;
+            }
+        }
+        neurons[..n_outputs].iter_mut().for_each(activate);
+
+        let mv = match creatures.arbitration {
+            Arbitration::Threshold => {
+                let hor_motion =
+                    neurons[OutputNeurons::MvE as usize] - neurons[OutputNeurons::MvW as usize];
+                let ver_motion =
+                    neurons[OutputNeurons::MvS as usize] - neurons[OutputNeurons::MvN as usize];
+
+                // both motions above threshold make a single diagonal move
+                let east = hor_motion > 0.5;
+                let west = hor_motion < -0.5;
+                let south = ver_motion > 0.5;
+                let north = ver_motion < -0.5;
+                match (east, west, north, south) {
+                    (true, _, true, _) => Some(Action::MoveNE(i)),
+                    (true, _, _, true) => Some(Action::MoveSE(i)),
+                    (_, true, true, _) => Some(Action::MoveNW(i)),
+                    (_, true, _, true) => Some(Action::MoveSW(i)),
+                    (true, ..) => Some(Action::MoveEast(i)),
+                    (_, true, ..) => Some(Action::MoveWest(i)),
+                    (_, _, true, _) => Some(Action::MoveNorth(i)),
+                    (.., true) => Some(Action::MoveSouth(i)),
+                    _ => None,
+                }
+            }
+            Arbitration::Softmax { temp } => {
+                let intents = [
+                    OutputNeurons::MvN,
+                    OutputNeurons::MvS,
+                    OutputNeurons::MvW,
+                    OutputNeurons::MvE,
+                    OutputNeurons::Wait,
+                ]
+                .map(|neuron| neurons[neuron as usize]);
+                let options = [
+                    Some(Action::MoveNorth(i)),
+                    Some(Action::MoveSouth(i)),
+                    Some(Action::MoveWest(i)),
+                    Some(Action::MoveEast(i)),
+                    None,
+                ];
+                options[softmax_pick(&intents, temp, rng)]
+            }
+        };
+        actions.extend(mv);
+
+        // the relative moves: turning first, so a creature can turn and move on in one step
+        let turn =
+            neurons[OutputNeurons::TurnLeft as usize] - neurons[OutputNeurons::TurnRight as usize];
+        if turn > 0.5 {
+            actions.push(Action::TurnLeft(i));
+        } else if turn < -0.5 {
+            actions.push(Action::TurnRight(i));
+        }
+        if neurons[OutputNeurons::MoveForward as usize] > 0.5 {
+            actions.push(Action::MoveForward(i));
+        }
+        if neurons[OutputNeurons::Deposit as usize] > 0.5 {
+            actions.push(Action::Deposit(i));
+        }
+    }
+}
+
+/// keeps an accumulated neuron input finite: infinities saturate at `±MAX_POTENTIAL`, `NaN`
+/// becomes `0.0`
+fn saturate(x: f32) -> f32 {
+    if x.is_nan() {
+        0.0
+    } else {
+        x.clamp(-MAX_POTENTIAL, MAX_POTENTIAL)
+    }
+}
+
+/// Picks an index at random, weighted by the softmax of `values` at temperature `temp`. A
+/// temperature of `0.0` (or below) picks the largest value, the first one on a tie.
+fn softmax_pick<R: Rng>(values: &[f32], temp: f32, rng: &mut R) -> usize {
+    let argmax = values
+        .iter()
+        .enumerate()
+        .fold(0, |best, (i, &v)| if v > values[best] { i } else { best });
+    if temp <= 0.0 {
+        return argmax;
+    }
+
+    // shifted by the maximum so the exponentials can't overflow
+    let weights: Vec<_> = values.iter().map(|&v| ((v - values[argmax]) / temp).exp()).collect();
+    let mut target = rng.gen_range(0.0..weights.iter().sum::<f32>());
+    for (i, &weight) in weights.iter().enumerate() {
+        if target < weight {
+            return i;
+        }
+        target -= weight;
+    }
+    argmax
 }
 
 impl Nucl for AndiN {
@@ -56,12 +463,12 @@ impl Nucl for AndiN {
         a.clone()
     }
 
+    /// flips a single random bit, every field of the encoding can mutate
     fn mutate<R>(&mut self, rng: &mut R)
     where
         R: rand::Rng,
     {
-        let bit = 2u32.pow(rng.gen_range(0..5));
-        self.encoded += bit;
+        self.mutate_bit(rng.gen_range(0..u32::BITS));
     }
 
     fn random<R>(rng: &mut R) -> Self
@@ -77,144 +484,283 @@ impl Nucl for AndiN {
 // the function
 //
 impl NeuronNucl for AndiN {
-    fn simulate<R: Rng>(creatures: &mut Creatures<AndiN>, rng: &mut R, width: i32, height: i32) {
-        // setup temp brain
-        let n_neurons = OutputNeurons::COUNT as usize;
-        let mut neurons: Vec<f32> = (0..n_neurons).into_iter().map(|_| 0.0).collect();
-        let mut actions = Vec::with_capacity(creatures.genoms.len());
-
-        for (i, (genom, pos)) in creatures
-            .genoms
-            .iter()
-            .zip(creatures.positions.iter())
-            .enumerate()
-        {
-            for nucl in genom.nucleotides.iter() {
-                let signal = match nucl.input() {
-                    InputNeurons::Osc => rng.gen_range(-1.0..1.0),
-                    InputNeurons::PL => {
-                        if pos.x < width / 2 {
-                            1.0
-                        } else {
-                            0.0
-                        }
-                    }
-                    InputNeurons::PR => {
-                        if pos.x >= width / 2 {
-                            1.0
-                        } else {
-                            0.0
-                        }
-                    }
-                    _ => 0.0,
-                } * nucl.weight();
+    type Scorer = AndiS;
+    type Scratch = AndiScratch;
 
-                neurons[nucl.output() as usize] += signal;
-            }
+    const BITS: u32 = u32::BITS;
 
-            for x in neurons.iter_mut() {
-                *x = x.tanh();
-            }
+    const MAX_WEIGHT: f32 = MAX_WEIGHT;
 
-            let hor_motion =
-                neurons[OutputNeurons::MvW as usize] - neurons[OutputNeurons::MvE as usize];
-            let ver_motion =
-                neurons[OutputNeurons::MvS as usize] - neurons[OutputNeurons::MvN as usize];
+    /// keeps the oscillator frequency, out of range neurons keep the current ones
+    fn with_synapse(&self, input: usize, output: usize, weight: f32) -> Self {
+        let input = InputNeurons::from_usize(input)
+            .filter(|neuron| !matches!(neuron, InputNeurons::COUNT))
+            .unwrap_or_else(|| self.input());
+        let output = OutputNeurons::from_usize(output)
+            .filter(|neuron| !matches!(neuron, OutputNeurons::COUNT))
+            .unwrap_or_else(|| self.output());
+        let frequency = self.encoded & (FREQ_MASK << FREQ_SHIFT);
+        Self {
+            encoded: AndiN::encode(input, output, weight).encoded | frequency,
+        }
+    }
 
-            if hor_motion > 0.5 {
-                actions.push(Action::MoveEast(i));
-            } else if hor_motion < -0.5 {
-                actions.push(Action::MoveWest(i));
-            }
-            if ver_motion > 0.5 {
-                actions.push(Action::MoveSouth(i));
-            } else if hor_motion < -0.5 {
-                actions.push(Action::MoveNorth(i));
-            }
+    /// without internal neurons bits 6 and 7 are ignored
+    fn synapse(&self, n_internal: usize) -> Synapse {
+        let internal = |flag: u32, index: u32| {
+            (n_internal > 0 && self.encoded & flag != 0)
+                .then(|| Neuron::Internal(index as usize % n_internal))
+        };
+        let source = internal(SOURCE_INTERNAL, self.field(INPUT_SHIFT, INPUT_MASK))
+            .unwrap_or(Neuron::Input(self.input() as usize));
+        let sink = internal(SINK_INTERNAL, self.field(OUTPUT_SHIFT, OUTPUT_MASK))
+            .unwrap_or(Neuron::Output(self.output() as usize));
+        Synapse {
+            source,
+            sink,
+            weight: self.weight(),
+        }
+    }
 
-            // reset brain for next individuum
-            for n in neurons.iter_mut() {
-                *n = 0.0;
+    fn acting_outputs() -> Vec<usize> {
+        [
+            OutputNeurons::MvN,
+            OutputNeurons::MvS,
+            OutputNeurons::MvW,
+            OutputNeurons::MvE,
+            OutputNeurons::Deposit,
+            OutputNeurons::MoveForward,
+            OutputNeurons::TurnLeft,
+            OutputNeurons::TurnRight,
+            OutputNeurons::Speed,
+        ]
+        .into_iter()
+        .map(|neuron| neuron as usize)
+        .collect()
+    }
+
+    fn bit_distance(&self, other: &Self) -> u32 {
+        (self.encoded ^ other.encoded).count_ones()
+    }
+
+    fn input_names() -> Vec<String> {
+        (0..InputNeurons::COUNT as u8)
+            .filter_map(InputNeurons::from_u8)
+            .map(|neuron| format!("{:?}", neuron))
+            .collect()
+    }
+
+    fn output_names() -> Vec<String> {
+        (0..OutputNeurons::COUNT as u8)
+            .filter_map(OutputNeurons::from_u8)
+            .map(|neuron| format!("{:?}", neuron))
+            .collect()
+    }
+
+    fn simulate<R: Rng>(
+        creatures: &mut Creatures<AndiN>,
+        _rng: &mut R,
+        board: &mut Board,
+        step: u32,
+    ) {
+        // the creatures draw from their own random number generators instead of `_rng`
+        board.decay_pheromone();
+        let mut scratch = std::mem::take(&mut creatures.scratch);
+        let mut rngs = std::mem::take(&mut creatures.rngs);
+        let start = Instant::now();
+        AndiN::decide_actions_into(creatures, &mut rngs, board, step, &mut scratch);
+        creatures.rngs = rngs;
+        let decided = Instant::now();
+        AndiN::apply_actions(creatures, &scratch.actions, board);
+        creatures.timings.decide += decided - start;
+        creatures.timings.apply += decided.elapsed();
+        creatures.activations.clone_from(&scratch.activations);
+        creatures.scratch = scratch;
+
+        for (pos, eaten) in creatures.positions.iter().zip(creatures.food_eaten.iter_mut()) {
+            if board.take_food(pos) {
+                *eaten += 1;
             }
         }
+    }
 
-        for action in actions.iter() {
-            match action {
-                Action::MoveEast(i) => creatures.positions[*i].x += 1,
-                Action::MoveWest(i) => creatures.positions[*i].x -= 1,
-                Action::MoveNorth(i) => creatures.positions[*i].y += 1,
-                Action::MoveSouth(i) => creatures.positions[*i].y -= 1,
-            };
+    fn score(creatures: &mut Creatures<Self>, board: &Board, zone: &dyn SelectionZone) {
+        for (genom, pos) in creatures.population.genoms.iter_mut().zip(creatures.positions.iter()) {
+            let distance = zone.distance(pos, board.width, board.height);
+            genom.set_score(AndiS::new(1.0 / (1.0 + distance)));
         }
+    }
 
-        // sanitize positions
-        for pos in creatures.positions.iter_mut() {
-            if pos.x < 0 {
-                pos.x = 0;
-            }
-            if pos.y < 0 {
-                pos.y = 0;
-            }
-            if pos.x >= width {
-                pos.x = width - 1;
-            }
-            if pos.y >= height {
-                pos.y = height - 1;
-            }
-        }
+    fn simulate_end<R: Rng>(
+        creatures: &mut Creatures<Self>,
+        rng: &mut R,
+        _board: &Board,
+        _zone: &dyn SelectionZone,
+    ) -> usize {
+        // if food is required only those who have eaten enough may become parents, if nobody
+        // has, the population's extinction policy decides
+        let candidates: Option<Vec<_>> = creatures.food_threshold.map(|threshold| {
+            creatures
+                .food_eaten
+                .iter()
+                .enumerate()
+                .filter(|(_, &eaten)| eaten > threshold)
+                .map(|(i, _)| i)
+                .collect()
+        });
+
+        // the next generation is back at full size, even if creatures died during this one
+        creatures.population.next_generation(candidates.as_deref(), rng)
     }
+}
 
-    fn simulate_end<R: Rng>(creatures: &mut Creatures<Self>, rng: &mut R, width: i32, height: i32) {
-        let n = creatures.genoms.len();
-        let parents: Vec<_> = creatures
-            .genoms
+impl<S: Scorer> Genom<AndiN, S> {
+    /// the number of cells a move takes the creature, from `1` to `MAX_SPEED`: one plus the summed
+    /// weights of the synapses into the `Speed` output, rounded
+    pub fn speed(&self) -> i32 {
+        let weights: f32 = self
+            .nucleotides
             .iter()
-            .zip(creatures.positions.iter())
-            .filter(|(genom, pos)| pos.x > width / 2)
-            .map(|(g, _)| g)
-            .collect();
-        println!("surviving parents: {}", parents.len());
+            .filter(|nucl| matches!(nucl.output(), OutputNeurons::Speed))
+            .map(|nucl| nucl.weight())
+            .sum();
+        1 + (weights.round() as i32).clamp(0, MAX_SPEED - 1)
+    }
+}
 
-        let mut partner: Vec<_> = parents
-            .iter()
-            .map(|p| (rng.next_u32(), p.clone()))
-            .collect();
-        partner.sort_by(|(a, _), (b, _)| a.cmp(b));
+// -------------------------------------------------------------------------------------------------
+// --- Hex Dump ------------------------------------------------------------------------------------
+// -------------------------------------------------------------------------------------------------
 
-        let mut new_genoms: Vec<_> = parents
-            .iter()
-            .zip(partner.iter())
-            .map(|(a, (_, b))| Genom::crossover(a, b, rng))
-            .collect();
+/// the error returned by [`Genom::from_hex_string`] for a word that isn't an encoded nucleotide
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseGenomError {
+    /// index of the offending nucleotide
+    pub index: usize,
+    pub word: String,
+}
 
-        while new_genoms.len() < n {
-            for (x, _) in partner.iter_mut() {
-                *x = rng.next_u32();
-            }
-            new_genoms.extend(parents
-                .iter()
-                .zip(partner.iter())
-                .map(|(a, (_, b))| Genom::crossover(b, a, rng)));
-        }
+impl Display for ParseGenomError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "nucleotide {} is not a 32 bit hex number: {:?}", self.index, self.word)
+    }
+}
+
+impl Error for ParseGenomError {}
 
-        creatures.genoms = new_genoms[..n].to_vec();
+impl<S: Scorer> Genom<AndiN, S> {
+    /// Dumps the nucleotides as 8 digit hex numbers, `HEX_PER_LINE` per line, e.g. to paste a
+    /// genom into an issue. Read it back with [`Genom::from_hex_string`].
+    pub fn to_hex_string(&self) -> String {
+        self.nucleotides
+            .chunks(HEX_PER_LINE)
+            .map(|line| {
+                line.iter().map(|n| format!("{:08x}", n.encoded)).collect::<Vec<_>>().join(" ")
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
     }
+
+    /// Parses a dump of [`Genom::to_hex_string`]. The nucleotides may be separated by any
+    /// whitespace, lines starting with `>` are comments (like a FASTA header).
+    pub fn from_hex_string(text: &str) -> Result<Self, ParseGenomError> {
+        text.lines()
+            .filter(|line| !line.trim_start().starts_with('>'))
+            .flat_map(str::split_whitespace)
+            .enumerate()
+            .map(|(index, word)| {
+                let word = word.trim_start_matches("0x");
+                match u32::from_str_radix(word, 16) {
+                    Ok(encoded) if word.len() <= 8 => Ok(AndiN::decode(encoded)),
+                    _ => Err(ParseGenomError {
+                        index,
+                        word: word.to_string(),
+                    }),
+                }
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .map(Genom::from_iter)
+    }
+}
+
+/// A 64 bit FNV-1a checksum over the encoded nucleotides (and lengths) of all `genoms`, in order.
+/// Unlike `DefaultHasher` it is stable across Rust versions, so two runs with the same seed can be
+/// compared by their checksums.
+pub fn population_checksum<S: Scorer>(genoms: &[Genom<AndiN, S>]) -> u64 {
+    const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+    const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+    let words = genoms.iter().flat_map(|genom| {
+        std::iter::once(genom.nucleotides.len() as u32)
+            .chain(genom.nucleotides.iter().map(|n| n.encoded))
+    });
+    words
+        .flat_map(u32::to_le_bytes)
+        .fold(FNV_OFFSET, |hash, byte| (hash ^ byte as u64).wrapping_mul(FNV_PRIME))
 }
 
 // -------------------------------------------------------------------------------------------------
 // --- Andis Scorer --------------------------------------------------------------------------------
 // -------------------------------------------------------------------------------------------------
 //
+#[derive(Debug, Clone)]
 pub struct AndiS {
     score_: f32,
 }
 
+impl AndiS {
+    pub fn new(score: f32) -> Self {
+        Self { score_: score }
+    }
+}
+
+impl From<f32> for AndiS {
+    fn from(score: f32) -> Self {
+        Self::new(score)
+    }
+}
+
 impl Scorer for AndiS {
     fn score(&self) -> f32 {
         self.score_
     }
 }
 
+// -------------------------------------------------------------------------------------------------
+// --- Solo Simulation -----------------------------------------------------------------------------
+// -------------------------------------------------------------------------------------------------
+
+/// Simulates the life of a single genom alone on an empty board, starting at `start`, and scores
+/// it by its final distance to `zone`. The same `seed` always gives the same score.
+#[derive(Debug, Clone)]
+pub struct SoloSimu {
+    pub width: i32,
+    pub height: i32,
+    pub start: (i32, i32),
+    pub steps: u32,
+    pub zone: Zone,
+    pub seed: u64,
+}
+
+impl Simu<AndiN, AndiS> for SoloSimu {
+    fn simulate(&self, genom: &mut Genom<AndiN, AndiS>) {
+        let mut board = Board::new(self.width, self.height);
+        let mut creatures = Creatures::from_genoms(
+            vec![Genom::from_previous(genom)],
+            vec![Point2::new(self.start.0, self.start.1)],
+        );
+        board.fill(&creatures.positions);
+
+        let mut rng = StdRng::seed_from_u64(self.seed);
+        for step in 0..self.steps {
+            AndiN::simulate(&mut creatures, &mut rng, &mut board, step);
+        }
+
+        let distance = self.zone.distance(&creatures.positions[0], self.width, self.height);
+        genom.set_score(AndiS::new(1.0 / (1.0 + distance)));
+    }
+}
+
 // -------------------------------------------------------------------------------------------------
 // --- Input Neurons -------------------------------------------------------------------------------
 // -------------------------------------------------------------------------------------------------
@@ -223,14 +769,26 @@ impl Scorer for AndiS {
 pub enum InputNeurons {
     PR,
     PL,
-    //WcF,
+    /// closeness of a wall or obstacle straight ahead in the direction of the last move
+    WcF,
     Osc,
+    /// normalised distance to the nearest wall
+    WallDist,
+    /// how crowded the surroundings within the sensor range are, closer creatures count more
+    CrowdDensity,
+    /// pheromone concentration around the creature's cell, closer cells count more
+    Pheromone,
+    /// x component of the unit vector towards the nearest creature in sensor range, `0` if none
+    NearestDx,
+    /// y component of the unit vector towards the nearest creature in sensor range, `0` if none
+    NearestDy,
     COUNT,
 }
 
 impl From<u32> for InputNeurons {
+    /// decodes the input field of an encoded [`AndiN`]
     fn from(encoded: u32) -> Self {
-        let byte = (encoded >> 24) as u8 % (InputNeurons::COUNT as u8);
+        let byte = ((encoded >> INPUT_SHIFT) & INPUT_MASK) as u8 % (InputNeurons::COUNT as u8);
 
         if let Some(neuron) = FromPrimitive::from_u8(byte) {
             neuron
@@ -241,8 +799,9 @@ impl From<u32> for InputNeurons {
 }
 
 impl Into<u32> for InputNeurons {
+    /// the input field of an encoded [`AndiN`]
     fn into(self) -> u32 {
-        (self as u32) << 24
+        (self as u32 & INPUT_MASK) << INPUT_SHIFT
     }
 }
 // -------------------------------------------------------------------------------------------------
@@ -256,12 +815,22 @@ pub enum OutputNeurons {
     MvW,
     MvE,
     Wait,
+    /// leaves pheromone behind on the creature's cell
+    Deposit,
+    /// moves in the direction the creature is facing
+    MoveForward,
+    TurnLeft,
+    TurnRight,
+    /// not fired but summed up: the weights of its synapses make the creature's speed, see
+    /// [`Genom::speed`]
+    Speed,
     COUNT,
 }
 
 impl From<u32> for OutputNeurons {
+    /// decodes the output field of an encoded [`AndiN`]
     fn from(encoded: u32) -> Self {
-        let byte = (encoded & 0xFF) as u8 % (OutputNeurons::COUNT as u8);
+        let byte = ((encoded >> OUTPUT_SHIFT) & OUTPUT_MASK) as u8 % (OutputNeurons::COUNT as u8);
 
         if let Some(neuron) = FromPrimitive::from_u8(byte) {
             neuron
@@ -272,8 +841,9 @@ impl From<u32> for OutputNeurons {
 }
 
 impl Into<u32> for OutputNeurons {
+    /// the output field of an encoded [`AndiN`]
     fn into(self) -> u32 {
-        self as u32
+        (self as u32 & OUTPUT_MASK) << OUTPUT_SHIFT
     }
 }
 
@@ -281,9 +851,822 @@ impl Into<u32> for OutputNeurons {
 // --- Actions -------------------------------------------------------------------------------------
 // -------------------------------------------------------------------------------------------------
 
-enum Action {
+/// what a creature decided to do in a step, holding the index of the creature
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
     MoveEast(usize),
     MoveWest(usize),
     MoveNorth(usize),
     MoveSouth(usize),
+    MoveNE(usize),
+    MoveNW(usize),
+    MoveSE(usize),
+    MoveSW(usize),
+    MoveForward(usize),
+    TurnLeft(usize),
+    TurnRight(usize),
+    Deposit(usize),
+}
+
+impl Action {
+    /// the index of the creature taking the action
+    pub fn creature(&self) -> usize {
+        match *self {
+            Action::MoveEast(i)
+            | Action::MoveWest(i)
+            | Action::MoveNorth(i)
+            | Action::MoveSouth(i)
+            | Action::MoveNE(i)
+            | Action::MoveNW(i)
+            | Action::MoveSE(i)
+            | Action::MoveSW(i)
+            | Action::MoveForward(i)
+            | Action::TurnLeft(i)
+            | Action::TurnRight(i)
+            | Action::Deposit(i) => i,
+        }
+    }
+}
+
+/// the buffers of [`AndiN::decide_actions_into`], kept from step to step
+#[derive(Default)]
+pub struct AndiScratch {
+    /// the positions of the creatures, rebuilt in place once they have moved
+    neighbors: SpatialGrid,
+    neurons: Vec<f32>,
+    actions: Vec<Action>,
+    /// the neurons of the watched creature
+    activations: Vec<f32>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::creature::{Activation, Direction, SpawnPattern};
+    use proptest::prelude::*;
+    use std::thread;
+
+    /// a single creature in the left half of a 16 x 16 board, its brain made of `nucleotides`
+    fn lone_creature(nucleotides: Vec<AndiN>) -> (Creatures<AndiN>, Board) {
+        let creatures =
+            Creatures::from_genoms(vec![Genom::from_iter(nucleotides)], vec![Point2::new(2, 8)]);
+        let mut board = Board::new(16, 16);
+        board.fill(&creatures.positions);
+        (creatures, board)
+    }
+
+    /// the actions of a lone creature whose `outputs` are wired to the constant `PL` input
+    fn actions<const N: usize>(
+        arbitration: Arbitration,
+        outputs: [OutputNeurons; N],
+    ) -> Vec<Action> {
+        let nucleotides = outputs
+            .into_iter()
+            .map(|output| AndiN::encode(InputNeurons::PL, output, MAX_WEIGHT))
+            .collect();
+        let (mut creatures, board) = lone_creature(nucleotides);
+        creatures.arbitration = arbitration;
+        AndiN::decide_actions(&creatures, &board, 0)
+    }
+
+    fn threshold_actions<const N: usize>(outputs: [OutputNeurons; N]) -> Vec<Action> {
+        actions(Arbitration::Threshold, outputs)
+    }
+
+    /// brainless creatures at `positions` on a 16 x 16 board, to carry out given actions
+    fn creatures_at(positions: &[(i32, i32)]) -> (Creatures<AndiN>, Board) {
+        let genoms = positions.iter().map(|_| Genom::from_iter([])).collect();
+        let positions = positions.iter().map(|&(x, y)| Point2::new(x, y)).collect();
+        let creatures = Creatures::from_genoms(genoms, positions);
+        let mut board = Board::new(16, 16);
+        board.fill(&creatures.positions);
+        (creatures, board)
+    }
+
+    /// `nucl` with the source and (or) the destination turned into an internal neuron
+    fn internal(nucl: AndiN, flags: u32) -> AndiN {
+        AndiN::decode(nucl.encoded | flags)
+    }
+
+    #[test]
+    fn threshold_moves_in_the_direction_of_the_neuron() {
+        assert_eq!(threshold_actions([OutputNeurons::MvE]), [Action::MoveEast(0)]);
+        assert_eq!(threshold_actions([OutputNeurons::MvW]), [Action::MoveWest(0)]);
+        assert_eq!(threshold_actions([OutputNeurons::MvN]), [Action::MoveNorth(0)]);
+        assert_eq!(threshold_actions([OutputNeurons::MvS]), [Action::MoveSouth(0)]);
+    }
+
+    #[test]
+    fn threshold_combines_two_directions_into_a_diagonal() {
+        use OutputNeurons::*;
+        assert_eq!(threshold_actions([MvN, MvE]), [Action::MoveNE(0)]);
+        assert_eq!(threshold_actions([MvN, MvW]), [Action::MoveNW(0)]);
+        assert_eq!(threshold_actions([MvS, MvE]), [Action::MoveSE(0)]);
+        assert_eq!(threshold_actions([MvS, MvW]), [Action::MoveSW(0)]);
+    }
+
+    #[test]
+    fn threshold_opposite_directions_cancel() {
+        assert_eq!(threshold_actions([OutputNeurons::MvE, OutputNeurons::MvW]), []);
+        assert_eq!(threshold_actions([]), []);
+    }
+
+    #[test]
+    fn softmax_near_zero_temperature_agrees_with_threshold() {
+        use OutputNeurons::*;
+        let softmax = Arbitration::Softmax { temp: 1.0e-3 };
+        assert_eq!(actions(softmax, [MvN]), threshold_actions([MvN]));
+        assert_eq!(actions(softmax, [MvS]), threshold_actions([MvS]));
+        assert_eq!(actions(softmax, [MvW]), threshold_actions([MvW]));
+        assert_eq!(actions(softmax, [MvE]), threshold_actions([MvE]));
+        assert_eq!(actions(softmax, [MvE]), [Action::MoveEast(0)]);
+    }
+
+    /// `n` random creatures on a 32 x 32 board, the same ones for the same `seed`
+    fn random_creatures(n: usize, seed: u64) -> (Creatures<AndiN>, Board) {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let spawn = SpawnPattern::default();
+        let mut creatures = Creatures::new(n, 16, 0, 1000, 32, 32, spawn, &mut rng);
+        // softmax arbitration, so the moves depend on the creatures' random number generators
+        creatures.arbitration = Arbitration::Softmax { temp: 1.0 };
+        let mut board = Board::new(32, 32);
+        board.fill(&creatures.positions);
+        (creatures, board)
+    }
+
+    fn coordinates(creatures: &Creatures<AndiN>) -> Vec<(i32, i32)> {
+        creatures.positions.iter().map(|pos| (pos.x, pos.y)).collect()
+    }
+
+    #[test]
+    fn parallel_and_sequential_evaluation_agree() {
+        const N: usize = 200;
+        const CHUNK: usize = 64;
+
+        let (mut sequential, mut board) = random_creatures(N, 7);
+        AndiN::simulate(&mut sequential, &mut StdRng::seed_from_u64(0), &mut board, 0);
+
+        let (mut parallel, mut board) = random_creatures(N, 7);
+        let neighbors = SpatialGrid::new(&parallel.positions, board.width, board.height, false);
+        let surroundings = Surroundings {
+            creatures: &parallel,
+            board: &board,
+            neighbors: &neighbors,
+            step: 0,
+        };
+        let mut rngs = parallel.rngs.clone();
+        let actions: Vec<_> = thread::scope(|scope| {
+            let handles: Vec<_> = rngs
+                .chunks_mut(CHUNK)
+                .enumerate()
+                .map(|(chunk, rngs)| {
+                    let surroundings = &surroundings;
+                    scope.spawn(move || {
+                        let (mut neurons, mut actions) = (Vec::new(), Vec::new());
+                        for (j, rng) in rngs.iter_mut().enumerate() {
+                            surroundings.decide(chunk * CHUNK + j, rng, &mut neurons, &mut actions);
+                        }
+                        actions
+                    })
+                })
+                .collect();
+            handles.into_iter().flat_map(|handle| handle.join().unwrap()).collect()
+        });
+        AndiN::apply_actions(&mut parallel, &actions, &mut board);
+
+        assert_ne!(coordinates(&parallel), coordinates(&random_creatures(N, 7).0));
+        assert_eq!(coordinates(&parallel), coordinates(&sequential));
+    }
+
+    #[test]
+    fn reused_buffers_decide_like_fresh_ones() {
+        let (mut creatures, mut board) = random_creatures(100, 11);
+        let mut scratch = AndiScratch::default();
+        for step in 0..20 {
+            let fresh = AndiN::decide_actions(&creatures, &board, step);
+            let mut rngs = std::mem::take(&mut creatures.rngs);
+            AndiN::decide_actions_into(&creatures, &mut rngs, &board, step, &mut scratch);
+            creatures.rngs = rngs;
+
+            assert_eq!(scratch.actions, fresh);
+            AndiN::apply_actions(&mut creatures, &scratch.actions, &mut board);
+        }
+    }
+
+    #[test]
+    fn synapse_decodes_internal_neurons_if_the_brain_has_some() {
+        let nucl = AndiN::encode(InputNeurons::Osc, OutputNeurons::TurnLeft, 1.0);
+        let to_internal = internal(nucl.clone(), SINK_INTERNAL);
+        let from_internal = internal(nucl.clone(), SOURCE_INTERNAL);
+        let (osc, turn_left) = (InputNeurons::Osc as usize, OutputNeurons::TurnLeft as usize);
+
+        assert_eq!(nucl.synapse(4).source, Neuron::Input(osc));
+        assert_eq!(nucl.synapse(4).sink, Neuron::Output(turn_left));
+        assert_eq!(to_internal.synapse(4).source, Neuron::Input(osc));
+        assert_eq!(to_internal.synapse(4).sink, Neuron::Internal(turn_left % 4));
+        assert_eq!(from_internal.synapse(2).source, Neuron::Internal(osc % 2));
+        assert_eq!(from_internal.synapse(2).sink, Neuron::Output(turn_left));
+
+        // without internal neurons the flags are ignored
+        assert_eq!(to_internal.synapse(0), nucl.synapse(0));
+        assert_eq!(from_internal.synapse(0), nucl.synapse(0));
+    }
+
+    #[test]
+    fn signals_pass_through_internal_neurons() {
+        // PL -> I0 and I0 -> MvE, read as PL -> MvN and PR -> MvE without internal neurons
+        let into_internal = internal(
+            AndiN::encode(InputNeurons::PL, OutputNeurons::MvN, MAX_WEIGHT),
+            SINK_INTERNAL,
+        );
+        let out_of_internal = internal(
+            AndiN::encode(InputNeurons::PR, OutputNeurons::MvE, MAX_WEIGHT),
+            SOURCE_INTERNAL,
+        );
+        let decide = |nucleotides: Vec<AndiN>, n_internal: usize| {
+            let (mut creatures, board) = lone_creature(nucleotides);
+            creatures.n_internal_neurons = n_internal;
+            AndiN::decide_actions(&creatures, &board, 0)
+        };
+
+        let two_hops = vec![into_internal.clone(), out_of_internal.clone()];
+        assert_eq!(decide(two_hops.clone(), 1), [Action::MoveEast(0)]);
+        assert_eq!(decide(two_hops, 0), [Action::MoveNorth(0)]);
+        // an internal neuron without inputs passes nothing on
+        assert_eq!(decide(vec![out_of_internal], 1), []);
+        assert_eq!(decide(vec![into_internal], 1), []);
+    }
+
+    #[test]
+    fn synapses_between_internal_neurons_are_dead() {
+        let both = SINK_INTERNAL | SOURCE_INTERNAL;
+        let genom: Genom<AndiN, AndiS> = Genom::from_iter([
+            internal(AndiN::encode(InputNeurons::PL, OutputNeurons::MvN, 1.0), SINK_INTERNAL),
+            internal(AndiN::encode(InputNeurons::PR, OutputNeurons::MvS, 1.0), both),
+            internal(AndiN::encode(InputNeurons::PR, OutputNeurons::MvE, 1.0), SOURCE_INTERNAL),
+        ]);
+        let mut pruned = genom.clone();
+
+        // I0 -> I1 goes, PL -> I0 -> MvE stays
+        assert_eq!(pruned.prune_dead_connections(2), 1);
+        let live = [genom.nucleotides[0].clone(), genom.nucleotides[2].clone()];
+        assert_eq!(pruned.nucleotides, live);
+    }
+
+    #[test]
+    fn display_tells_inputs_internal_neurons_and_outputs_apart() {
+        let nucl = AndiN::encode(InputNeurons::PL, OutputNeurons::MvE, 0.75);
+        assert_eq!(
+            nucl.to_string(),
+            "\x1b[32mPL\x1b[0m \x1b[90m0.75\x1b[0m -> \x1b[33mMvE\x1b[0m"
+        );
+        assert_eq!(
+            internal(nucl.clone(), SINK_INTERNAL).to_string(),
+            "\x1b[32mPL\x1b[0m \x1b[90m0.75\x1b[0m -> \x1b[36mI3\x1b[0m"
+        );
+        assert_eq!(
+            internal(nucl, SOURCE_INTERNAL).to_string(),
+            "\x1b[36mI1\x1b[0m \x1b[90m0.75\x1b[0m -> \x1b[33mMvE\x1b[0m"
+        );
+    }
+
+    proptest! {
+        #[test]
+        fn encode_round_trips_through_decode(
+            input in 0..InputNeurons::COUNT as u32,
+            output in 0..OutputNeurons::COUNT as u32,
+            weight in -MAX_WEIGHT..=MAX_WEIGHT,
+        ) {
+            let (input, output) = (input << INPUT_SHIFT, output << OUTPUT_SHIFT);
+            let encoded = AndiN::encode(input.into(), output.into(), weight).encoded;
+
+            let nucl = AndiN::decode(encoded);
+            let decoded: (u32, u32) = (nucl.input().into(), nucl.output().into());
+            prop_assert_eq!(decoded, (input, output));
+            prop_assert!((nucl.weight() - weight).abs() <= 2.0 * MAX_WEIGHT / 65536.0);
+            prop_assert_eq!(nucl.frequency(), 0.0);
+        }
+
+        #[test]
+        fn population_checksum_is_stable(
+            encoded in prop::collection::vec(prop::collection::vec(any::<u32>(), 0..16), 1..8),
+            bit in 0..u32::BITS,
+        ) {
+            let genoms: Vec<Genom<AndiN, AndiS>> = encoded
+                .iter()
+                .map(|genom| Genom::from_iter(genom.iter().copied().map(AndiN::decode)))
+                .collect();
+            let checksum = population_checksum(&genoms);
+            let copies: Vec<_> = genoms.iter().map(Genom::from_previous).collect();
+            prop_assert_eq!(population_checksum(&copies), checksum);
+
+            // any flipped bit changes the checksum
+            let mut mutated = copies;
+            if let Some(nucl) = mutated.iter_mut().find_map(|genom| genom.nucleotides.first_mut()) {
+                nucl.mutate_bit(bit);
+                prop_assert_ne!(population_checksum(&mutated), checksum);
+            }
+        }
+    }
+
+    #[test]
+    fn only_one_of_two_creatures_moves_into_the_same_cell() {
+        let (mut creatures, mut board) = creatures_at(&[(2, 8), (4, 8)]);
+        let actions = [Action::MoveEast(0), Action::MoveWest(1)];
+        AndiN::apply_actions(&mut creatures, &actions, &mut board);
+
+        assert_eq!(coordinates(&creatures), [(3, 8), (4, 8)]);
+        assert_eq!(board.occupant(2, 8), None);
+        assert_eq!(board.occupant(3, 8), Some(0));
+        assert_eq!(board.occupant(4, 8), Some(1));
+    }
+
+    #[test]
+    fn a_slow_oscillator_keeps_moving_the_same_way() {
+        let nucl = AndiN::encode(InputNeurons::Osc, OutputNeurons::MvE, MAX_WEIGHT);
+        assert_eq!(nucl.frequency(), 0.0);
+        let (mut creatures, mut board) = lone_creature(vec![nucl]);
+        creatures.phases[0] = std::f32::consts::FRAC_PI_2;
+
+        for step in 0..10 {
+            let actions = AndiN::decide_actions(&creatures, &board, step);
+            assert_eq!(actions, [Action::MoveEast(0)], "step {}", step);
+            AndiN::apply_actions(&mut creatures, &actions, &mut board);
+        }
+        assert_eq!(coordinates(&creatures), [(12, 8)]);
+    }
+
+    #[test]
+    fn moving_off_a_toroidal_board_wraps_around() {
+        let (mut creatures, mut board) = creatures_at(&[(0, 8)]);
+        board.boundary = BoundaryMode::Wrap;
+        AndiN::apply_actions(&mut creatures, &[Action::MoveWest(0)], &mut board);
+
+        assert_eq!(coordinates(&creatures), [(15, 8)]);
+        assert_eq!(board.occupant(15, 8), Some(0));
+    }
+
+    #[test]
+    fn creatures_fleeing_crowds_spread_out() {
+        // two columns next to each other, the left one wired to flee west, the right one east
+        let positions: Vec<_> = (6..9).flat_map(|y| [(7, y), (8, y)]).collect();
+        let (mut creatures, mut board) = creatures_at(&positions);
+        for (genom, &(x, _)) in creatures.population.genoms.iter_mut().zip(&positions) {
+            let flee = if x == 7 { OutputNeurons::MvW } else { OutputNeurons::MvE };
+            let nucl = AndiN::encode(InputNeurons::CrowdDensity, flee, MAX_WEIGHT);
+            *genom = Genom::from_iter([nucl]);
+        }
+        let crowding = |creatures: &Creatures<AndiN>, board: &Board| -> usize {
+            creatures.positions.iter().map(|pos| board.count_neighbors(pos, 1)).sum()
+        };
+
+        let before = crowding(&creatures, &board);
+        for step in 0..10 {
+            let actions = AndiN::decide_actions(&creatures, &board, step);
+            AndiN::apply_actions(&mut creatures, &actions, &mut board);
+        }
+        let after = crowding(&creatures, &board);
+        assert!(after < before, "{} neighbors before, {} after", before, after);
+
+        // once spread out, nobody is crowded enough to move any more
+        assert_eq!(AndiN::decide_actions(&creatures, &board, 10), []);
+    }
+
+    #[test]
+    fn a_creature_moving_onto_food_eats_it() {
+        // a 2 x 1 board, the food can only go next to the creature
+        let brain = Genom::from_iter([AndiN::encode(InputNeurons::PL, OutputNeurons::MvE, 1.0)]);
+        let mut creatures = Creatures::from_genoms(vec![brain], vec![Point2::new(0, 0)]);
+        let mut board = Board::new(2, 1);
+        board.fill(&creatures.positions);
+        board.food_count = 1;
+        board.scatter_food(&mut StdRng::seed_from_u64(0));
+        assert_eq!(board.food().len(), 1);
+
+        AndiN::simulate(&mut creatures, &mut StdRng::seed_from_u64(0), &mut board, 0);
+        assert_eq!(coordinates(&creatures), [(1, 0)]);
+        assert_eq!(creatures.food_eaten, [1]);
+        assert!(board.food().is_empty());
+    }
+
+    #[test]
+    fn creatures_cannot_cross_a_wall() {
+        let nucl = AndiN::encode(InputNeurons::PL, OutputNeurons::MvE, MAX_WEIGHT);
+        let (mut creatures, mut board) = lone_creature(vec![nucl]);
+        for y in 0..16 {
+            assert!(board.add_obstacle(8, y));
+        }
+
+        for step in 0..20 {
+            AndiN::simulate(&mut creatures, &mut StdRng::seed_from_u64(0), &mut board, step);
+        }
+        assert_eq!(coordinates(&creatures), [(7, 8)]);
+    }
+
+    #[test]
+    fn pruning_removes_only_the_dead_connection() {
+        let live = [
+            AndiN::encode(InputNeurons::PL, OutputNeurons::MvE, MAX_WEIGHT),
+            AndiN::encode(InputNeurons::Osc, OutputNeurons::MvN, 1.0),
+        ];
+        // nothing reads the `Wait` output
+        let dead = AndiN::encode(InputNeurons::PL, OutputNeurons::Wait, 1.0);
+        let mut genom: Genom<AndiN, AndiS> =
+            Genom::from_iter([live[0].clone(), dead, live[1].clone()]);
+        let before = genom.clone();
+
+        assert_eq!(genom.prune_dead_connections(0), 1);
+        assert_eq!(genom.nucleotides, live);
+
+        let mut pruned = lone_creature(genom.nucleotides);
+        let mut original = lone_creature(before.nucleotides);
+        let mut rng = StdRng::seed_from_u64(0);
+        for step in 0..20 {
+            AndiN::simulate(&mut pruned.0, &mut rng, &mut pruned.1, step);
+            AndiN::simulate(&mut original.0, &mut rng, &mut original.1, step);
+            assert_eq!(coordinates(&pruned.0), coordinates(&original.0));
+        }
+    }
+
+    #[test]
+    fn mutate_flips_a_single_bit_and_reaches_every_bit() {
+        let mut rng = StdRng::seed_from_u64(3);
+        let mut nucl = AndiN::decode(0x1234_5678);
+        let mut flipped = 0u32;
+        for _ in 0..1000 {
+            let before = nucl.encoded;
+            nucl.mutate(&mut rng);
+            let changed = before ^ nucl.encoded;
+            assert_eq!(changed.count_ones(), 1);
+            flipped |= changed;
+        }
+        assert_eq!(flipped, u32::MAX);
+    }
+
+    #[test]
+    fn the_fields_of_the_encoding_do_not_alias() {
+        let nucl = AndiN::encode(InputNeurons::WcF, OutputNeurons::MvS, -1.5);
+
+        // flipping any bit of the weight leaves the input and output alone
+        for bit in WEIGHT_SHIFT..WEIGHT_SHIFT + 16 {
+            let mut mutated = nucl.clone();
+            mutated.mutate_bit(bit);
+            assert!(matches!(mutated.input(), InputNeurons::WcF));
+            assert!(matches!(mutated.output(), OutputNeurons::MvS));
+            assert_ne!(mutated.weight(), nucl.weight());
+        }
+        // and flipping a bit of the input or output leaves the weight alone
+        for bit in [INPUT_SHIFT, INPUT_SHIFT + 3, OUTPUT_SHIFT, OUTPUT_SHIFT + 5] {
+            let mut mutated = nucl.clone();
+            mutated.mutate_bit(bit);
+            assert_eq!(mutated.weight(), nucl.weight());
+        }
+    }
+
+    #[test]
+    fn encode_reads_back_the_fields() {
+        let nucl = AndiN::encode(InputNeurons::Osc, OutputNeurons::MvE, 0.75);
+
+        assert!(matches!(nucl.input(), InputNeurons::Osc));
+        assert!(matches!(nucl.output(), OutputNeurons::MvE));
+        let step = 2.0 * MAX_WEIGHT / 65536.0;
+        assert!((nucl.weight() - 0.75).abs() <= step);
+        // out of range weights are clamped
+        let weight = |weight| AndiN::encode(InputNeurons::Osc, OutputNeurons::MvE, weight).weight();
+        assert_eq!(weight(-100.0), -MAX_WEIGHT);
+        assert_eq!(weight(100.0), MAX_WEIGHT - step);
+    }
+
+    #[test]
+    fn a_negative_weight_inhibits_the_output() {
+        let nucl = AndiN::encode(InputNeurons::PL, OutputNeurons::MvE, -MAX_WEIGHT);
+        let (mut creatures, mut board) = lone_creature(vec![nucl]);
+
+        assert_eq!(AndiN::decide_actions(&creatures, &board, 0), [Action::MoveWest(0)]);
+        AndiN::simulate(&mut creatures, &mut StdRng::seed_from_u64(0), &mut board, 0);
+        assert_eq!(creatures.positions[0].x, 1);
+    }
+
+    #[test]
+    fn deposited_pheromone_decays_the_next_step() {
+        let nucl = AndiN::encode(InputNeurons::PL, OutputNeurons::Deposit, MAX_WEIGHT);
+        let (mut creatures, mut board) = lone_creature(vec![nucl]);
+        board.pheromone_decay = 0.5;
+        // where `lone_creature` puts the creature
+        let pos = Point2::new(2, 8);
+
+        AndiN::simulate(&mut creatures, &mut StdRng::seed_from_u64(0), &mut board, 0);
+        assert_eq!(board.pheromone_at(&pos), PHEROMONE_DEPOSIT);
+        assert_eq!(board.pheromone_at(&Point2::new(pos.x + 1, pos.y)), 0.0);
+
+        // without the deposit neuron the pheromone only decays
+        creatures.population.genoms[0].nucleotides.clear();
+        AndiN::simulate(&mut creatures, &mut StdRng::seed_from_u64(0), &mut board, 1);
+        assert_eq!(board.pheromone_at(&pos), PHEROMONE_DEPOSIT * 0.5);
+    }
+
+    #[test]
+    fn hex_dumps_round_trip() {
+        let mut rng = StdRng::seed_from_u64(4);
+        let genom: Genom<AndiN, AndiS> = Genom::random(20, &mut rng);
+        let dump = genom.to_hex_string();
+        assert_eq!(dump.lines().count(), 3);
+
+        let parsed: Genom<AndiN, AndiS> = Genom::from_hex_string(&dump).unwrap();
+        assert_eq!(parsed.nucleotides, genom.nucleotides);
+        // any whitespace and a header will do
+        let pasted = format!(">a genom from an issue\n  {}\n", dump.replace(' ', "\t\n "));
+        let parsed: Genom<AndiN, AndiS> = Genom::from_hex_string(&pasted).unwrap();
+        assert_eq!(parsed.nucleotides, genom.nucleotides);
+    }
+
+    #[test]
+    fn malformed_hex_dumps_are_rejected() {
+        let parse = |text| Genom::<AndiN, AndiS>::from_hex_string(text).map(|_| ());
+        let error = |index, word: &str| {
+            Err(ParseGenomError {
+                index,
+                word: word.to_string(),
+            })
+        };
+
+        assert_eq!(parse("0000ff01 xyz"), error(1, "xyz"));
+        assert_eq!(parse("123456789"), error(0, "123456789"));
+        assert_eq!(parse("0x"), error(0, ""));
+        assert_eq!(parse("0x0000ff01 ff"), Ok(()));
+    }
+
+    #[test]
+    fn moving_off_a_clamped_board_stays_at_the_edge() {
+        let (mut creatures, mut board) = creatures_at(&[(0, 8), (15, 15)]);
+        assert_eq!(board.boundary, BoundaryMode::Clamp);
+        let actions = [Action::MoveWest(0), Action::MoveNE(1)];
+        AndiN::apply_actions(&mut creatures, &actions, &mut board);
+
+        assert_eq!(coordinates(&creatures), [(0, 8), (15, 15)]);
+        assert_eq!(board.occupant(0, 8), Some(0));
+
+        // the same moves on a toroidal board
+        board.boundary = BoundaryMode::Wrap;
+        AndiN::apply_actions(&mut creatures, &actions, &mut board);
+        assert_eq!(coordinates(&creatures), [(15, 8), (0, 0)]);
+    }
+
+    #[test]
+    fn two_directions_above_threshold_make_one_diagonal_step() {
+        let nucleotides = [OutputNeurons::MvN, OutputNeurons::MvE]
+            .into_iter()
+            .map(|output| AndiN::encode(InputNeurons::PL, output, MAX_WEIGHT))
+            .collect();
+        let (mut creatures, mut board) = lone_creature(nucleotides);
+
+        let actions = AndiN::decide_actions(&creatures, &board, 0);
+        assert_eq!(actions, [Action::MoveNE(0)]);
+        AndiN::apply_actions(&mut creatures, &actions, &mut board);
+        assert_eq!(coordinates(&creatures), [(3, 9)]);
+    }
+
+    #[test]
+    fn iter_connections_decodes_every_nucleotide() {
+        let genom: Genom<AndiN, AndiS> = Genom::from_iter([
+            AndiN::encode(InputNeurons::PL, OutputNeurons::MvE, 1.5),
+            AndiN::encode(InputNeurons::WallDist, OutputNeurons::Wait, -0.25),
+            internal(AndiN::encode(InputNeurons::Osc, OutputNeurons::MvS, 3.0), SINK_INTERNAL),
+        ]);
+
+        let connections: Vec<_> = genom.iter_connections(2).collect();
+        assert_eq!(connections.len(), 3);
+        for (synapse, nucl) in connections.iter().zip(&genom.nucleotides) {
+            assert_eq!(synapse.source, Neuron::Input(nucl.input() as usize));
+            assert_eq!(synapse.weight, nucl.weight());
+        }
+        let (mv_e, wait) = (OutputNeurons::MvE as usize, OutputNeurons::Wait as usize);
+        assert_eq!(connections[0].sink, Neuron::Output(mv_e));
+        assert_eq!(connections[1].sink, Neuron::Output(wait));
+        assert_eq!(connections[2].sink, Neuron::Internal(OutputNeurons::MvS as usize % 2));
+        assert_eq!(connections[1].weight, -0.25);
+    }
+
+    /// the neurons of creature `i` deciding at `step`, computed directly rather than by `simulate`
+    fn neurons_of(creatures: &Creatures<AndiN>, board: &Board, i: usize, step: u32) -> Vec<f32> {
+        let wrap = board.boundary == BoundaryMode::Wrap;
+        let neighbors = SpatialGrid::new(&creatures.positions, board.width, board.height, wrap);
+        let surroundings = Surroundings {
+            creatures,
+            board,
+            neighbors: &neighbors,
+            step,
+        };
+        let mut neurons = Vec::new();
+        let mut rng = creatures.rngs[i].clone();
+        surroundings.decide(i, &mut rng, &mut neurons, &mut Vec::new());
+        neurons
+    }
+
+    #[test]
+    fn wcf_is_high_facing_a_wall_and_zero_facing_open_space() {
+        // both heading east, one right before the east wall and one far from it
+        let (mut creatures, board) = creatures_at(&[(14, 8), (2, 8)]);
+        let nucl = AndiN::encode(InputNeurons::WcF, OutputNeurons::MvE, 1.0);
+        for genom in creatures.population.genoms.iter_mut() {
+            *genom = Genom::from_iter([nucl.clone()]);
+        }
+        creatures.headings = vec![(1, 0); 2];
+        creatures.activation = Activation::Identity;
+        let wcf = |creatures: &Creatures<AndiN>, i| {
+            neurons_of(creatures, &board, i, 0)[OutputNeurons::MvE as usize]
+        };
+
+        let facing_the_wall = wcf(&creatures, 0);
+        assert_eq!(facing_the_wall, 1.0 - 1.0 / WCF_RANGE as f32);
+        assert_eq!(wcf(&creatures, 1), 0.0);
+        // turned around, the first one sees open space and the second one the west wall
+        creatures.headings = vec![(-1, 0); 2];
+        assert_eq!(wcf(&creatures, 0), 0.0);
+        assert!(wcf(&creatures, 1) > 0.0);
+        assert!(wcf(&creatures, 1) < facing_the_wall);
+    }
+
+    #[test]
+    fn moving_forward_follows_the_turns() {
+        let (mut creatures, mut board) = creatures_at(&[(8, 8)]);
+        assert_eq!(creatures.facing[0], Direction::East);
+        let mut act = |creatures: &mut Creatures<AndiN>, actions: &[Action]| {
+            AndiN::apply_actions(creatures, actions, &mut board);
+            coordinates(creatures)[0]
+        };
+
+        assert_eq!(act(&mut creatures, &[Action::MoveForward(0)]), (9, 8));
+        // north is `+y`
+        let turn_left = [Action::TurnLeft(0), Action::MoveForward(0)];
+        assert_eq!(act(&mut creatures, &turn_left), (9, 9));
+        assert_eq!(creatures.facing[0], Direction::North);
+        let turn_around = [Action::TurnRight(0), Action::TurnRight(0), Action::MoveForward(0)];
+        assert_eq!(act(&mut creatures, &turn_around), (9, 8));
+        assert_eq!(creatures.facing[0], Direction::South);
+    }
+
+    #[test]
+    fn simulate_records_the_activations_of_the_watched_creature() {
+        let (mut creatures, mut board) = random_creatures(50, 3);
+        let expected = neurons_of(&creatures, &board, 7, 0);
+        creatures.watched = Some(7);
+
+        AndiN::simulate(&mut creatures, &mut StdRng::seed_from_u64(0), &mut board, 0);
+        assert_eq!(creatures.activations, expected);
+        assert!(creatures.activations.iter().any(|&a| a != 0.0));
+
+        creatures.watched = None;
+        AndiN::simulate(&mut creatures, &mut StdRng::seed_from_u64(0), &mut board, 1);
+        assert!(creatures.activations.is_empty());
+    }
+
+    #[test]
+    fn a_speed_3_creature_moves_3_cells_unless_blocked() {
+        let (mut creatures, mut board) = creatures_at(&[(2, 4), (2, 8), (2, 12), (4, 12)]);
+        let fast = Genom::from_iter([AndiN::encode(InputNeurons::PL, OutputNeurons::Speed, 2.0)]);
+        assert_eq!(fast.speed(), 3);
+        for genom in &mut creatures.population.genoms[..3] {
+            *genom = fast.clone();
+        }
+        assert!(board.add_obstacle(4, 8));
+
+        let actions = [Action::MoveEast(0), Action::MoveEast(1), Action::MoveEast(2)];
+        AndiN::apply_actions(&mut creatures, &actions, &mut board);
+        // blocked by the obstacle and by the creature in the way, none of them tunnels through
+        assert_eq!(coordinates(&creatures), [(5, 4), (3, 8), (3, 12), (4, 12)]);
+    }
+
+    #[test]
+    fn a_creature_with_energy_2_makes_only_2_of_5_moves() {
+        let (mut creatures, mut board) = creatures_at(&[(2, 8)]);
+        creatures.energy[0] = 2;
+
+        for _ in 0..5 {
+            AndiN::apply_actions(&mut creatures, &[Action::MoveEast(0)], &mut board);
+        }
+        assert_eq!(coordinates(&creatures), [(4, 8)]);
+        assert_eq!(creatures.energy[0], 0);
+        // turning doesn't move the creature, so it costs nothing and is still possible
+        AndiN::apply_actions(&mut creatures, &[Action::TurnLeft(0)], &mut board);
+        assert_eq!(creatures.facing[0], Direction::North);
+    }
+
+    #[test]
+    fn identity_keeps_the_raw_sum_and_relu_clamps_negatives() {
+        // on the left half, so both synapses see a `PL` of 1.0
+        let (mut creatures, board) = creatures_at(&[(2, 8)]);
+        creatures.population.genoms[0] = Genom::from_iter([
+            AndiN::encode(InputNeurons::PL, OutputNeurons::MvE, 1.5),
+            AndiN::encode(InputNeurons::PL, OutputNeurons::MvE, -3.0),
+            AndiN::encode(InputNeurons::PL, OutputNeurons::MvW, 2.5),
+        ]);
+        let outputs = |creatures: &Creatures<AndiN>| {
+            let neurons = neurons_of(creatures, &board, 0, 0);
+            (neurons[OutputNeurons::MvE as usize], neurons[OutputNeurons::MvW as usize])
+        };
+
+        creatures.activation = Activation::Identity;
+        assert_eq!(outputs(&creatures), (-1.5, 2.5));
+        creatures.activation = Activation::ReLU;
+        assert_eq!(outputs(&creatures), (0.0, 2.5));
+        creatures.activation = Activation::Tanh;
+        assert_eq!(outputs(&creatures), ((-1.5f32).tanh(), 2.5f32.tanh()));
+    }
+
+    #[test]
+    fn a_predator_moving_onto_a_prey_removes_it_from_the_selection() {
+        // two predators (species 0) and two prey (species 1), the first prey right next to the
+        // first predator
+        let (mut creatures, mut board) = creatures_at(&[(2, 8), (12, 2), (3, 8), (10, 10)]);
+        creatures.population.set_species(2);
+        creatures.predators = Some(0);
+        let marker = |i| AndiN::encode(InputNeurons::PL, OutputNeurons::Wait, i as f32);
+        for (i, genom) in creatures.population.genoms.iter_mut().enumerate() {
+            *genom = Genom::from_iter([marker(i)]);
+        }
+
+        AndiN::apply_actions(&mut creatures, &[Action::MoveEast(0)], &mut board);
+        assert_eq!(coordinates(&creatures), [(3, 8), (12, 2), (10, 10)]);
+        assert_eq!(creatures.population.species, [0, 0, 1]);
+        assert_eq!(creatures.food_eaten[0], 1);
+        assert_eq!(board.occupant(3, 8), Some(0));
+
+        // the prey left over is the only parent of its species
+        assert_ne!(marker(2), marker(3));
+        let population = &mut creatures.population;
+        population.next_generation(None, &mut StdRng::seed_from_u64(1));
+        for (genom, &species) in population.genoms.iter().zip(&population.species) {
+            if species == 1 {
+                assert_eq!(genom.nucleotides, [marker(3)]);
+            }
+        }
+    }
+
+    #[test]
+    fn mutate_bit_and_mutate_at_change_only_what_they_are_told() {
+        let mut nucl = AndiN::decode(0x1234_5678);
+        nucl.mutate_bit(5);
+        assert_eq!(nucl.encoded ^ 0x1234_5678, 1 << 5);
+
+        let nucleotides: Vec<_> = (0..5).map(|i| AndiN::decode(0x1111_1111 * i)).collect();
+        let mut genom: Genom<AndiN, AndiS> = Genom::from_iter(nucleotides.clone());
+        genom.mutate_at(2, &mut StdRng::seed_from_u64(4));
+        for (i, (mutated, original)) in genom.nucleotides.iter().zip(&nucleotides).enumerate() {
+            assert_eq!(mutated != original, i == 2, "nucleotide {}", i);
+        }
+    }
+
+    #[test]
+    fn nearest_points_east_to_a_neighbor_in_the_east() {
+        let (mut creatures, board) = creatures_at(&[(4, 8), (6, 8)]);
+        creatures.population.genoms[0] = Genom::from_iter([
+            AndiN::encode(InputNeurons::NearestDx, OutputNeurons::MvE, 1.0),
+            AndiN::encode(InputNeurons::NearestDy, OutputNeurons::MvS, 1.0),
+        ]);
+        creatures.activation = Activation::Identity;
+
+        let neurons = neurons_of(&creatures, &board, 0, 0);
+        let (dx, dy) = (neurons[OutputNeurons::MvE as usize], neurons[OutputNeurons::MvS as usize]);
+        assert!(dx > 0.9, "NearestDx {}", dx);
+        assert!(dy.abs() < 1e-3, "NearestDy {}", dy);
+
+        // with nobody in sensor range both are 0
+        creatures.sensor_range = 1;
+        let neurons = neurons_of(&creatures, &board, 0, 0);
+        assert_eq!(neurons[OutputNeurons::MvE as usize], 0.0);
+        assert_eq!(neurons[OutputNeurons::MvS as usize], 0.0);
+    }
+
+    #[test]
+    fn extreme_weights_do_not_poison_the_movement_decision() {
+        // non-finite potentials saturate before any activation sees them
+        for x in [f32::INFINITY, f32::NEG_INFINITY, f32::NAN, f32::MAX] {
+            for activation in Activation::ALL {
+                assert!(activation.apply(saturate(x)).is_finite(), "{:?} of {}", activation, x);
+            }
+        }
+
+        // a brain of nothing but extreme weights, pushing east through an internal neuron too
+        let (mut creatures, board) = creatures_at(&[(2, 8)]);
+        let east = |weight| AndiN::encode(InputNeurons::PL, OutputNeurons::MvE, weight);
+        let mut nucleotides = vec![east(f32::MAX); 500];
+        nucleotides.push(internal(east(f32::MAX), SINK_INTERNAL));
+        nucleotides.push(internal(east(f32::INFINITY), SOURCE_INTERNAL));
+        nucleotides.push(east(f32::NAN));
+        creatures.population.genoms[0] = Genom::from_iter(nucleotides);
+        creatures.n_internal_neurons = 1;
+
+        for activation in Activation::ALL {
+            creatures.activation = activation;
+            let neurons = neurons_of(&creatures, &board, 0, 0);
+            assert!(neurons.iter().all(|x| x.is_finite()), "{:?}: {:?}", activation, neurons);
+            // a silent sigmoid is 0.5, so MvW cancels out half of MvE and nobody moves at all
+            if !matches!(activation, Activation::Sigmoid) {
+                let actions = AndiN::decide_actions(&creatures, &board, 0);
+                let moves_east = matches!(actions[..], [Action::MoveEast(0)]);
+                assert!(moves_east, "{:?}: {:?}", activation, actions);
+            }
+        }
+    }
 }
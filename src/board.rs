@@ -0,0 +1,425 @@
+use rand::Rng;
+
+use crate::{creature::Point2, spatial::falloff};
+
+/// the highest pheromone concentration a cell can hold
+const MAX_PHEROMONE: f32 = 1.0;
+
+/// what happens to creatures moving off the board
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum BoundaryMode {
+    /// they stay at the edge
+    #[default]
+    Clamp,
+    /// they reappear on the opposite side (toroidal board)
+    Wrap,
+}
+
+// -------------------------------------------------------------------------------------------------
+// --- Board ---------------------------------------------------------------------------------------
+// -------------------------------------------------------------------------------------------------
+
+/// The board the creatures live on.
+///
+/// Besides the dimensions it keeps an occupancy grid which marks the cells a creature sits on, so
+/// that no two creatures can share a cell, and the index of that creature.
+/// It also holds the food pellets lying around, the obstacles no creature can walk onto and the
+/// pheromones the creatures leave behind.
+pub struct Board {
+    pub width: i32,
+    pub height: i32,
+    pub boundary: BoundaryMode,
+    occupied: OccupancyGrid,
+    /// the index of the creature on each cell, only meaningful where `occupied` is set
+    grid: Vec<usize>,
+    /// the food pellets, `has_food` marks their cells for quick lookup
+    food: Vec<Point2>,
+    has_food: Vec<bool>,
+    /// number of food pellets scattered each generation
+    pub food_count: usize,
+    /// the obstacle cells, `blocked` marks them for quick lookup
+    obstacles: Vec<Point2>,
+    blocked: Vec<bool>,
+    /// pheromone concentration per cell, in `[0, 1]`
+    pheromone: Vec<f32>,
+    /// factor by which the pheromones decay each step
+    pub pheromone_decay: f32,
+}
+
+impl Board {
+    pub fn new(width: i32, height: i32) -> Self {
+        Self {
+            width,
+            height,
+            boundary: BoundaryMode::default(),
+            occupied: OccupancyGrid::new(width, height),
+            grid: vec![0; (width * height) as usize],
+            food: Vec::new(),
+            has_food: vec![false; (width * height) as usize],
+            food_count: 0,
+            obstacles: Vec::new(),
+            blocked: vec![false; (width * height) as usize],
+            pheromone: vec![0.0; (width * height) as usize],
+            pheromone_decay: 0.9,
+        }
+    }
+
+    pub fn contains(&self, x: i32, y: i32) -> bool {
+        x >= 0 && y >= 0 && x < self.width && y < self.height
+    }
+
+    /// maps a (possibly off-board) position back onto the board according to the boundary mode
+    pub fn resolve(&self, x: i32, y: i32) -> Point2 {
+        match self.boundary {
+            BoundaryMode::Clamp => {
+                Point2::new(x.clamp(0, self.width - 1), y.clamp(0, self.height - 1))
+            }
+            BoundaryMode::Wrap => Point2::new(x.rem_euclid(self.width), y.rem_euclid(self.height)),
+        }
+    }
+
+    fn index(&self, x: i32, y: i32) -> usize {
+        (x + self.width * y) as usize
+    }
+
+    // --- occupancy -------------------------------------------------------------------------------
+
+    /// returns the index of the creature at `(x, y)`, `None` if the cell is empty or off the board
+    pub fn occupant(&self, x: i32, y: i32) -> Option<usize> {
+        if !self.contains(x, y) {
+            return None;
+        }
+        self.occupied.is_set(x, y).then(|| self.grid[self.index(x, y)])
+    }
+
+    /// `true` if `(x, y)` is on the board and neither occupied nor an obstacle
+    pub fn is_free(&self, x: i32, y: i32) -> bool {
+        if !self.contains(x, y) {
+            return false;
+        }
+        !self.occupied.is_set(x, y) && !self.blocked[self.index(x, y)]
+    }
+
+    /// moves creature `i` from `from` to `to`. The caller has to make sure `to` is free.
+    pub fn move_occupant(&mut self, i: usize, from: &Point2, to: &Point2) {
+        self.occupied.unset(from.x, from.y);
+        self.occupied.set(to.x, to.y);
+        let to = self.index(to.x, to.y);
+        self.grid[to] = i;
+    }
+
+    /// frees the cell at `pos`, e.g. when its creature has been killed
+    pub fn vacate(&mut self, pos: &Point2) {
+        self.occupied.unset(pos.x, pos.y);
+    }
+
+    /// counts the creatures in the square of side `2 * radius + 1` around `pos`, not counting the
+    /// one at `pos` itself. Wraps around the edges on a toroidal board.
+    pub fn count_neighbors(&self, pos: &Point2, radius: i32) -> usize {
+        let mut count = 0;
+        for dy in -radius..=radius {
+            for dx in -radius..=radius {
+                if dx == 0 && dy == 0 {
+                    continue;
+                }
+                let (x, y) = (pos.x + dx, pos.y + dy);
+                let (x, y) = match self.boundary {
+                    BoundaryMode::Clamp if !self.contains(x, y) => continue,
+                    BoundaryMode::Clamp => (x, y),
+                    BoundaryMode::Wrap => (x.rem_euclid(self.width), y.rem_euclid(self.height)),
+                };
+                if self.occupied.is_set(x, y) {
+                    count += 1;
+                }
+            }
+        }
+        count
+    }
+
+    /// distance to the nearest edge of the board, normalised to `[0, 1]` (`1` being the center)
+    pub fn wall_distance(&self, pos: &Point2) -> f32 {
+        let distance = pos.x.min(pos.y).min(self.width - 1 - pos.x).min(self.height - 1 - pos.y);
+        let max_distance = ((self.width.min(self.height) - 1) / 2).max(1);
+        distance as f32 / max_distance as f32
+    }
+
+    /// How close the nearest wall or obstacle is when looking from `pos` in direction `(dx, dy)`,
+    /// normalised to `[0, 1]`: `1` if it is right in front, `0` if there is none within `range`
+    /// cells (or no direction). The edges of a wrapping board aren't walls.
+    pub fn wall_ahead(&self, pos: &Point2, dx: i32, dy: i32, range: i32) -> f32 {
+        if (dx, dy) == (0, 0) || range <= 0 {
+            return 0.0;
+        }
+        let (mut x, mut y) = (pos.x, pos.y);
+        for distance in 0..range {
+            x += dx;
+            y += dy;
+            if !self.contains(x, y) {
+                if self.boundary == BoundaryMode::Clamp {
+                    return 1.0 - distance as f32 / range as f32;
+                }
+                let wrapped = self.resolve(x, y);
+                x = wrapped.x;
+                y = wrapped.y;
+            }
+            if self.is_obstacle(x, y) {
+                return 1.0 - distance as f32 / range as f32;
+            }
+        }
+        0.0
+    }
+
+    /// rebuilds the occupancy grid from scratch. Panics if a position is off the board, which
+    /// would otherwise silently alias a cell in another row.
+    pub fn fill(&mut self, positions: &[Point2]) {
+        self.occupied.clear_all();
+        for (i, pos) in positions.iter().enumerate() {
+            assert!(
+                self.contains(pos.x, pos.y),
+                "position ({}, {}) is off the {}x{} board",
+                pos.x,
+                pos.y,
+                self.width,
+                self.height
+            );
+            self.occupied.set(pos.x, pos.y);
+            let index = self.index(pos.x, pos.y);
+            self.grid[index] = i;
+        }
+    }
+
+    // --- food ------------------------------------------------------------------------------------
+
+    pub fn food(&self) -> &[Point2] {
+        &self.food
+    }
+
+    /// removes all food and scatters `food_count` new pellets on free cells
+    pub fn scatter_food<R: Rng>(&mut self, rng: &mut R) {
+        for pellet in self.food.drain(..) {
+            let index = (pellet.x + self.width * pellet.y) as usize;
+            self.has_food[index] = false;
+        }
+
+        let free_cells = self
+            .blocked
+            .iter()
+            .filter(|&&blocked| !blocked)
+            .count()
+            .saturating_sub(self.occupied.count());
+        let n = self.food_count.min(free_cells);
+        while self.food.len() < n {
+            let x = rng.gen_range(0..self.width);
+            let y = rng.gen_range(0..self.height);
+            let index = self.index(x, y);
+            if self.is_free(x, y) && !self.has_food[index] {
+                self.has_food[index] = true;
+                self.food.push(Point2::new(x, y));
+            }
+        }
+    }
+
+    /// eats the food pellet at `pos`, returns `false` if there is none
+    pub fn take_food(&mut self, pos: &Point2) -> bool {
+        let index = self.index(pos.x, pos.y);
+        if !self.has_food[index] {
+            return false;
+        }
+        self.has_food[index] = false;
+        if let Some(i) = self.food.iter().position(|f| f.x == pos.x && f.y == pos.y) {
+            self.food.swap_remove(i);
+        }
+        true
+    }
+
+    // --- obstacles -------------------------------------------------------------------------------
+
+    pub fn obstacles(&self) -> &[Point2] {
+        &self.obstacles
+    }
+
+    pub fn is_obstacle(&self, x: i32, y: i32) -> bool {
+        self.contains(x, y) && self.blocked[self.index(x, y)]
+    }
+
+    /// turns `(x, y)` into an obstacle, returns `false` if it is off the board or already one.
+    /// Food lying there is removed, a creature sitting there has to be moved away by the caller.
+    pub fn add_obstacle(&mut self, x: i32, y: i32) -> bool {
+        if !self.contains(x, y) || self.is_obstacle(x, y) {
+            return false;
+        }
+        let index = self.index(x, y);
+        self.blocked[index] = true;
+        self.obstacles.push(Point2::new(x, y));
+        self.take_food(&Point2::new(x, y));
+        true
+    }
+
+    pub fn clear_obstacles(&mut self) {
+        for obstacle in self.obstacles.drain(..) {
+            let index = (obstacle.x + self.width * obstacle.y) as usize;
+            self.blocked[index] = false;
+        }
+    }
+
+    // --- pheromones ------------------------------------------------------------------------------
+
+    /// the pheromone concentration of every cell, row by row
+    pub fn pheromone(&self) -> &[f32] {
+        &self.pheromone
+    }
+
+    /// the pheromone concentration at `pos`, `0.0` off the board
+    pub fn pheromone_at(&self, pos: &Point2) -> f32 {
+        if !self.contains(pos.x, pos.y) {
+            return 0.0;
+        }
+        self.pheromone[self.index(pos.x, pos.y)]
+    }
+
+    /// The pheromone concentration in the square of side `2 * radius + 1` around `pos`, averaged
+    /// with each cell weighted by its [`falloff`]. Cells off the board don't count, on a toroidal
+    /// board they wrap around.
+    pub fn pheromone_around(&self, pos: &Point2, radius: i32) -> f32 {
+        let (mut sum, mut weights) = (0.0, 0.0);
+        for dy in -radius..=radius {
+            for dx in -radius..=radius {
+                let (x, y) = (pos.x + dx, pos.y + dy);
+                let (x, y) = match self.boundary {
+                    BoundaryMode::Clamp if !self.contains(x, y) => continue,
+                    BoundaryMode::Clamp => (x, y),
+                    BoundaryMode::Wrap => (x.rem_euclid(self.width), y.rem_euclid(self.height)),
+                };
+                let weight = falloff(dx, dy);
+                sum += self.pheromone[self.index(x, y)] * weight;
+                weights += weight;
+            }
+        }
+        if weights > 0.0 {
+            sum / weights
+        } else {
+            0.0
+        }
+    }
+
+    /// adds `amount` of pheromone at `pos`, the concentration saturates at `1.0`
+    pub fn deposit_pheromone(&mut self, pos: &Point2, amount: f32) {
+        if !self.contains(pos.x, pos.y) {
+            return;
+        }
+        let index = self.index(pos.x, pos.y);
+        self.pheromone[index] = (self.pheromone[index] + amount).min(MAX_PHEROMONE);
+    }
+
+    /// lets all pheromones decay by one step
+    pub fn decay_pheromone(&mut self) {
+        for concentration in self.pheromone.iter_mut() {
+            *concentration *= self.pheromone_decay;
+        }
+    }
+
+    pub fn clear_pheromone(&mut self) {
+        for concentration in self.pheromone.iter_mut() {
+            *concentration = 0.0;
+        }
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+// --- Occupancy Grid ------------------------------------------------------------------------------
+// -------------------------------------------------------------------------------------------------
+
+/// One bit per cell of a board, marking the occupied ones. Clearing it only touches
+/// `width * height / 64` words.
+pub struct OccupancyGrid {
+    width: i32,
+    height: i32,
+    bits: Vec<u64>,
+}
+
+impl OccupancyGrid {
+    pub fn new(width: i32, height: i32) -> Self {
+        Self {
+            width,
+            height,
+            bits: vec![0; ((width * height) as usize).div_ceil(64)],
+        }
+    }
+
+    /// the word and the bit within it of `(x, y)`, `None` if it is off the grid
+    fn bit(&self, x: i32, y: i32) -> Option<(usize, u64)> {
+        if x < 0 || y < 0 || x >= self.width || y >= self.height {
+            return None;
+        }
+        let index = (x + self.width * y) as usize;
+        Some((index / 64, 1 << (index % 64)))
+    }
+
+    /// marks `(x, y)` as occupied, does nothing if it is off the grid
+    pub fn set(&mut self, x: i32, y: i32) {
+        if let Some((word, bit)) = self.bit(x, y) {
+            self.bits[word] |= bit;
+        }
+    }
+
+    pub fn unset(&mut self, x: i32, y: i32) {
+        if let Some((word, bit)) = self.bit(x, y) {
+            self.bits[word] &= !bit;
+        }
+    }
+
+    /// `true` if `(x, y)` is occupied, `false` if it is off the grid
+    pub fn is_set(&self, x: i32, y: i32) -> bool {
+        self.bit(x, y).is_some_and(|(word, bit)| self.bits[word] & bit != 0)
+    }
+
+    pub fn clear_all(&mut self) {
+        for word in self.bits.iter_mut() {
+            *word = 0;
+        }
+    }
+
+    /// number of occupied cells
+    pub fn count(&self) -> usize {
+        self.bits.iter().map(|word| word.count_ones() as usize).sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    #[test]
+    fn occupancy_grid_agrees_with_a_vec_of_bools() {
+        // not a multiple of 64 cells, so the last word is partly unused
+        let (width, height) = (13, 7);
+        let mut grid = OccupancyGrid::new(width, height);
+        let mut cells = vec![false; (width * height) as usize];
+        let mut rng = StdRng::seed_from_u64(0);
+
+        for _ in 0..500 {
+            let (x, y) = (rng.gen_range(0..width), rng.gen_range(0..height));
+            let occupied = rng.gen_bool(0.6);
+            if occupied {
+                grid.set(x, y);
+            } else {
+                grid.unset(x, y);
+            }
+            cells[(x + width * y) as usize] = occupied;
+        }
+        for y in 0..height {
+            for x in 0..width {
+                assert_eq!(grid.is_set(x, y), cells[(x + width * y) as usize], "({}, {})", x, y);
+            }
+        }
+        assert_eq!(grid.count(), cells.iter().filter(|&&occupied| occupied).count());
+
+        // off the grid nothing is set, and setting it doesn't alias a cell in another row
+        grid.clear_all();
+        grid.set(width, 0);
+        grid.set(-1, 1);
+        assert!(!grid.is_set(width, 0) && !grid.is_set(0, 1) && !grid.is_set(width - 1, 0));
+        assert_eq!(grid.count(), 0);
+    }
+}
@@ -0,0 +1,162 @@
+use egui::{vec2, Align2, Color32, Pos2, Sense, Stroke, TextStyle, Ui};
+
+/// a neuron in a creature's brain, indexed within its layer
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Neuron {
+    Input(usize),
+    Internal(usize),
+    Output(usize),
+}
+
+/// a weighted connection between two neurons, as encoded by a single nucleotide
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Synapse {
+    pub source: Neuron,
+    pub sink: Neuron,
+    pub weight: f32,
+}
+
+/// vertical space per neuron in [`draw_brain`]
+const ROW_HEIGHT: f32 = 22.0;
+
+/// horizontal space reserved for the neuron names in [`draw_brain`]
+const LABEL_WIDTH: f32 = 90.0;
+
+/// `true` if `neuron` receives a signal from the inputs, `fed` marks the internal neurons that do
+fn is_fed(neuron: Neuron, fed: &[bool]) -> bool {
+    match neuron {
+        Neuron::Input(_) => true,
+        Neuron::Internal(i) => fed.get(i).copied().unwrap_or(false),
+        Neuron::Output(_) => false,
+    }
+}
+
+/// `true` if `neuron` passes its signal on to the acting outputs, `feeding` marks the internal
+/// neurons that do
+fn is_feeding(neuron: Neuron, feeding: &[bool], acting_outputs: &[usize]) -> bool {
+    match neuron {
+        Neuron::Input(_) => false,
+        Neuron::Internal(i) => feeding.get(i).copied().unwrap_or(false),
+        Neuron::Output(i) => acting_outputs.contains(&i),
+    }
+}
+
+/// `false` for a synapse between two internal neurons: they form a single layer between the
+/// inputs and the outputs, so such a synapse carries nothing
+fn is_forward(synapse: &Synapse) -> bool {
+    !matches!((synapse.source, synapse.sink), (Neuron::Internal(_), Neuron::Internal(_)))
+}
+
+/// Marks the synapses that take part in a path from an input to one of the `acting_outputs`. The
+/// others (e.g. ending in an internal neuron that feeds nothing) are pruned, they can't influence
+/// the creature's behaviour.
+pub fn live_synapses(
+    synapses: &[Synapse],
+    n_internal: usize,
+    acting_outputs: &[usize],
+) -> Vec<bool> {
+    let mut fed = vec![false; n_internal];
+    let mut feeding = vec![false; n_internal];
+
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for synapse in synapses.iter().filter(|synapse| is_forward(synapse)) {
+            if let Neuron::Internal(i) = synapse.sink {
+                if i < n_internal && !fed[i] && is_fed(synapse.source, &fed) {
+                    fed[i] = true;
+                    changed = true;
+                }
+            }
+            if let Neuron::Internal(i) = synapse.source {
+                if i < n_internal
+                    && !feeding[i]
+                    && is_feeding(synapse.sink, &feeding, acting_outputs)
+                {
+                    feeding[i] = true;
+                    changed = true;
+                }
+            }
+        }
+    }
+
+    synapses
+        .iter()
+        .map(|synapse| {
+            is_forward(synapse)
+                && is_fed(synapse.source, &fed)
+                && is_feeding(synapse.sink, &feeding, acting_outputs)
+        })
+        .collect()
+}
+
+/// Draws a brain as node-link diagram: inputs on the left, internal neurons in the middle and
+/// outputs on the right. The stronger a synapse the thicker and darker it is drawn, pruned
+/// synapses are drawn faintly.
+pub fn draw_brain(
+    ui: &mut Ui,
+    synapses: &[Synapse],
+    input_names: &[String],
+    n_internal: usize,
+    output_names: &[String],
+    acting_outputs: &[usize],
+) {
+    let rows = input_names.len().max(n_internal).max(output_names.len()).max(1);
+    let size = vec2(ui.available_width(), rows as f32 * ROW_HEIGHT);
+    let (response, painter) = ui.allocate_painter(size, Sense::hover());
+    let rect = response.rect;
+
+    let column = |x: f32, n: usize| {
+        let top = rect.top() + (rows - n) as f32 * ROW_HEIGHT / 2.0;
+        move |i: usize| Pos2::new(x, top + (i as f32 + 0.5) * ROW_HEIGHT)
+    };
+    let input_pos = column(rect.left() + LABEL_WIDTH, input_names.len());
+    let internal_pos = column(rect.center().x, n_internal);
+    let output_pos = column(rect.right() - LABEL_WIDTH, output_names.len());
+    let pos = |neuron: Neuron| match neuron {
+        Neuron::Input(i) => input_pos(i),
+        Neuron::Internal(i) => internal_pos(i),
+        Neuron::Output(i) => output_pos(i),
+    };
+
+    let live = live_synapses(synapses, n_internal, acting_outputs);
+    for (synapse, live) in synapses.iter().zip(live) {
+        let stroke = if live {
+            let strength = synapse.weight.abs().min(1.0);
+            let color = if synapse.weight < 0.0 {
+                Color32::from_rgb(200, 60, 60)
+            } else {
+                Color32::from_rgb(60, 140, 60)
+            };
+            Stroke::new(1.0 + 3.0 * strength, color.linear_multiply(0.3 + 0.7 * strength))
+        } else {
+            Stroke::new(1.0, Color32::from_gray(90).linear_multiply(0.3))
+        };
+        painter.line_segment([pos(synapse.source), pos(synapse.sink)], stroke);
+    }
+
+    let node_color = Color32::LIGHT_BLUE;
+    for (i, name) in input_names.iter().enumerate() {
+        painter.circle_filled(input_pos(i), 5.0, node_color);
+        painter.text(
+            input_pos(i) - vec2(8.0, 0.0),
+            Align2::RIGHT_CENTER,
+            name,
+            TextStyle::Body,
+            node_color,
+        );
+    }
+    for i in 0..n_internal {
+        painter.circle_filled(internal_pos(i), 5.0, node_color);
+    }
+    for (i, name) in output_names.iter().enumerate() {
+        painter.circle_filled(output_pos(i), 5.0, node_color);
+        painter.text(
+            output_pos(i) + vec2(8.0, 0.0),
+            Align2::LEFT_CENTER,
+            name,
+            TextStyle::Body,
+            node_color,
+        );
+    }
+}
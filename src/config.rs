@@ -0,0 +1,140 @@
+#[cfg(feature = "serde")]
+use serde::Deserialize;
+#[cfg(feature = "serde")]
+use std::{fs, io, path::Path};
+
+use rand::rngs::StdRng;
+
+use crate::{
+    creature::SpawnPattern, world::WorldBuilder, zone::Zone, BHEIGHT, BWIDTH, GENOME_LENGTH,
+    MUT_COEFF, N_CREATURES, N_INTERNAL_NEURONS, STEPS_IN_GENERATION,
+};
+
+// -------------------------------------------------------------------------------------------------
+// --- Simulation Config ---------------------------------------------------------------------------
+// -------------------------------------------------------------------------------------------------
+
+/// The parameters of a simulation run.
+///
+/// With the `serde` feature it can be loaded from a TOML file, e.g.
+///
+/// ```toml
+/// creatures = 500
+/// width = 300
+/// height = 100
+/// zone = { CenterCircle = 40 }
+/// seed = 42
+/// ```
+///
+/// Parameters missing from the file keep their defaults, the constants in `main.rs`.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Deserialize), serde(default))]
+pub struct SimConfig {
+    pub creatures: usize,
+    pub genome_length: usize,
+    pub internal_neurons: usize,
+    pub mutation_coeff: usize,
+    pub width: i32,
+    pub height: i32,
+    pub steps_in_generation: u32,
+    pub zone: Zone,
+    /// where the creatures are placed, e.g. `spawn = { Column = 0 }`
+    pub spawn: SpawnPattern,
+    /// seed of the random number generator, a random one is used if not given
+    pub seed: Option<u64>,
+    /// number of threads breeding the next generation, the children don't depend on it
+    pub threads: usize,
+}
+
+impl Default for SimConfig {
+    fn default() -> Self {
+        Self {
+            creatures: N_CREATURES,
+            genome_length: GENOME_LENGTH,
+            internal_neurons: N_INTERNAL_NEURONS,
+            mutation_coeff: MUT_COEFF,
+            width: BWIDTH as i32,
+            height: BHEIGHT as i32,
+            steps_in_generation: STEPS_IN_GENERATION,
+            zone: Zone::default(),
+            spawn: SpawnPattern::default(),
+            seed: None,
+            threads: 1,
+        }
+    }
+}
+
+impl SimConfig {
+    /// a [`WorldBuilder`] set up with this config
+    pub fn builder(&self) -> WorldBuilder<StdRng> {
+        let builder = WorldBuilder::new()
+            .creatures(self.creatures)
+            .genome_length(self.genome_length)
+            .internal_neurons(self.internal_neurons)
+            .mutation_coeff(self.mutation_coeff)
+            .size(self.width, self.height)
+            .steps_in_generation(self.steps_in_generation)
+            .zone(self.zone.clone())
+            .spawn(self.spawn.clone())
+            .threads(self.threads);
+        match self.seed {
+            Some(seed) => builder.seed(seed),
+            None => builder,
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl SimConfig {
+    pub fn from_toml(text: &str) -> Result<Self, toml::de::Error> {
+        toml::from_str(text)
+    }
+
+    /// reads the config from the TOML file at `path`
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let text = fs::read_to_string(path)?;
+        Self::from_toml(&text).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+    }
+}
+
+#[cfg(all(test, feature = "serde"))]
+mod tests {
+    use super::*;
+    use crate::{andis::AndiN, genes::Genom, world::World};
+
+    const SAMPLE: &str = r#"
+        creatures = 50
+        genome_length = 6
+        width = 300
+        height = 100
+        zone = { CenterCircle = 40 }
+        seed = 42
+        threads = 4
+    "#;
+
+    #[test]
+    fn a_sample_toml_configures_the_world() {
+        let config = SimConfig::from_toml(SAMPLE).unwrap();
+        assert_eq!(config.seed, Some(42));
+        assert!(matches!(config.zone, Zone::CenterCircle(40)));
+        assert_eq!(config.mutation_coeff, MUT_COEFF);
+
+        let world: World<_, AndiN> = config.builder().build();
+        assert_eq!(world.board_size(), (300, 100));
+        assert_eq!(world.creatures.positions.len(), 50);
+        assert_eq!(world.creatures.population.threads, 4);
+
+        // the same seed without the config gives the same creatures
+        let seeded: World<_, AndiN> = WorldBuilder::new()
+            .creatures(50)
+            .genome_length(6)
+            .size(300, 100)
+            .zone(Zone::CenterCircle(40))
+            .seed(42)
+            .build();
+        let genoms = |world: &World<_, AndiN>| -> Vec<_> {
+            world.creatures.genoms().iter().map(Genom::to_hex_string).collect()
+        };
+        assert_eq!(genoms(&world), genoms(&seeded));
+    }
+}
@@ -1,6 +1,18 @@
-use rand::{Rng};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+#[cfg(feature = "serde")]
+use serde::Deserialize;
+use std::{f32::consts::TAU, hash::Hash, time::Duration};
 
-use crate::genes::{Genom, Nucl, Scorer};
+use crate::{
+    board::Board,
+    brain::{live_synapses, Synapse},
+    genes::{Genom, Nucl, Scorer, Simu},
+    objective::Objective,
+    population::Population,
+    traits::Traits,
+    utils::stream_seed,
+    zone::SelectionZone,
+};
 
 // add a dummy type for point2. likely we won't ever need more than that, but for the case a more
 // sophisticated type (like nalgebra::Point2) is needed, we have the option to typedef it in.
@@ -15,53 +27,666 @@ impl Point2 {
 
 type Point = Point2;
 
+/// the way a creature is facing, for the moves relative to it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Direction {
+    North,
+    #[default]
+    East,
+    South,
+    West,
+}
+
+impl Direction {
+    /// the step `(dx, dy)` of a move forward, north is `+y`
+    pub fn delta(self) -> (i32, i32) {
+        match self {
+            Direction::North => (0, 1),
+            Direction::East => (1, 0),
+            Direction::South => (0, -1),
+            Direction::West => (-1, 0),
+        }
+    }
+
+    /// the direction after a quarter turn counter-clockwise
+    pub fn left(self) -> Self {
+        match self {
+            Direction::North => Direction::West,
+            Direction::East => Direction::North,
+            Direction::South => Direction::East,
+            Direction::West => Direction::South,
+        }
+    }
+
+    /// the direction after a quarter turn clockwise
+    pub fn right(self) -> Self {
+        self.left().left().left()
+    }
+}
+
+/// how the movement outputs of a brain are turned into a move
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Arbitration {
+    /// moves along an axis if the difference of its two opposite outputs exceeds `0.5`
+    #[default]
+    Threshold,
+    /// Picks one of the movement outputs (or waiting) at random, weighted by the softmax of the
+    /// activations at temperature `temp`. The lower the temperature the more likely the strongest
+    /// output wins, at `0.0` it always does.
+    Softmax { temp: f32 },
+}
+
+/// the activation function squashing the summed input of a neuron
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Activation {
+    #[default]
+    Tanh,
+    /// `max(0, x)`
+    ReLU,
+    /// `1 / (1 + e^-x)`, in `(0, 1)`
+    Sigmoid,
+    /// the summed input as it is
+    Identity,
+}
+
+impl Activation {
+    /// all activation functions, in the order they are offered in the UI
+    pub const ALL: [Activation; 4] = [
+        Activation::Tanh,
+        Activation::ReLU,
+        Activation::Sigmoid,
+        Activation::Identity,
+    ];
+
+    pub fn apply(self, x: f32) -> f32 {
+        match self {
+            Activation::Tanh => x.tanh(),
+            Activation::ReLU => x.max(0.0),
+            Activation::Sigmoid => 1.0 / (1.0 + (-x).exp()),
+            Activation::Identity => x,
+        }
+    }
+}
+
 // The creatures struct
 //
-pub struct Creatures<N: Nucl> {
-    pub genoms: Vec<Genom<N, NullScorer>>,
+/// A [`Population`] living on a board: besides the genoms it keeps the per-creature state, all
+/// vectors are index-aligned with `population.genoms`.
+pub struct Creatures<N: NeuronNucl> {
+    pub population: Population<N, N::Scorer>,
     pub positions: Vec<Point>,
-    pub mutation_coeff: usize,
+    /// oscillator phase of each creature, randomised at the start of each generation
+    pub phases: Vec<f32>,
+    /// random number generator of each creature, seeded from a master seed and the creature's
+    /// index at the start of each generation, so it doesn't depend on the order of evaluation
+    pub rngs: Vec<StdRng>,
+    /// direction `(dx, dy)` of each creature's last move, `(0, 0)` until it tries to move
+    pub headings: Vec<(i32, i32)>,
+    /// the way each creature is facing, changed by turning only
+    pub facing: Vec<Direction>,
+    /// number of food pellets (or, for predators, prey) each creature has eaten during this
+    /// generation
+    pub food_eaten: Vec<u32>,
+    /// number of steps each creature has lived through in this generation
+    pub ages: Vec<u32>,
+    /// if set, creatures older than this die in the middle of the generation
+    pub max_age: Option<u32>,
+    /// number of moves each creature has left in this generation
+    pub energy: Vec<i32>,
+    /// if set, the number of moves a creature can make per generation, otherwise unlimited
+    pub max_energy: Option<i32>,
+    /// number of internal neurons per brain, in addition to the input and output neurons
+    pub n_internal_neurons: usize,
+    /// how far (in cells, along both axes) the senses for other creatures and pheromones reach
+    pub sensor_range: i32,
+    /// the activation function of the neurons
+    pub activation: Activation,
+    /// how the movement outputs decide on a move
+    pub arbitration: Arbitration,
+    /// if set, only creatures that have eaten more food than this may become parents
+    pub food_threshold: Option<u32>,
+    /// if set, the creatures of this species are predators: moving onto another creature kills
+    /// it, and they are scored by the number of prey they caught
+    pub predators: Option<usize>,
+    /// where the creatures are placed at the start of each generation
+    pub spawn: SpawnPattern,
+    /// reusable buffers of [`NeuronNucl::simulate`], not part of the creatures' state
+    pub scratch: N::Scratch,
+    /// the creature whose neuron activations `simulate` records in `activations`, it is followed
+    /// through [`Creatures::retain`] until it dies
+    pub watched: Option<usize>,
+    /// the output neuron activations of the `watched` creature in the last step, empty if none
+    pub activations: Vec<f32>,
+    /// time spent simulating the running generation so far, `simulate` adds to it
+    pub timings: PhaseTimings,
+}
+
+/// number of tries to find a free cell following the [`SpawnPattern`] before a creature is placed
+/// anywhere on the board instead
+const SPAWN_TRIES: usize = 100;
+
+/// default of [`Creatures::sensor_range`]
+const DEFAULT_SENSOR_RANGE: i32 = 2;
+
+/// where the creatures are placed at the start of a generation
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(Deserialize))]
+pub enum SpawnPattern {
+    /// anywhere on the board
+    #[default]
+    Uniform,
+    /// in the column `x`
+    Column(i32),
+    /// a gaussian blob around `center`
+    Blob { center: (i32, i32), sigma: f32 },
+    /// on a circle around the center of the board, a third of the shorter side away from it
+    Ring,
+}
+
+impl SpawnPattern {
+    /// a random cell following the pattern, not necessarily on the board
+    fn sample<R: Rng>(&self, world_width: i32, world_height: i32, rng: &mut R) -> Point {
+        match self {
+            SpawnPattern::Uniform => {
+                Point::new(rng.gen_range(0..world_width), rng.gen_range(0..world_height))
+            }
+            SpawnPattern::Column(x) => Point::new(*x, rng.gen_range(0..world_height)),
+            SpawnPattern::Blob { center, sigma } => {
+                // Box-Muller transform
+                let radius = sigma * (-2.0 * (1.0 - rng.gen::<f32>()).ln()).sqrt();
+                let angle = rng.gen_range(0.0..TAU);
+                Point::new(
+                    center.0 + (radius * angle.cos()).round() as i32,
+                    center.1 + (radius * angle.sin()).round() as i32,
+                )
+            }
+            SpawnPattern::Ring => {
+                let radius = world_width.min(world_height) as f32 / 3.0;
+                let angle = rng.gen_range(0.0..TAU);
+                Point::new(
+                    world_width / 2 + (radius * angle.cos()).round() as i32,
+                    world_height / 2 + (radius * angle.sin()).round() as i32,
+                )
+            }
+        }
+    }
+}
+
+/// Scatters `n` creatures over the board following the `spawn` pattern, no two of them share a
+/// cell and none sits on one of the `obstacles`. A creature for which the pattern has no free cell
+/// left is placed anywhere on the board.
+pub fn make_positions<R: Rng>(
+    n: usize,
+    world_width: i32,
+    world_height: i32,
+    obstacles: &[Point],
+    spawn: &SpawnPattern,
+    rng: &mut R,
+) -> Vec<Point> {
+    let mut taken = vec![false; (world_width * world_height) as usize];
+    for obstacle in obstacles {
+        taken[(obstacle.x + world_width * obstacle.y) as usize] = true;
+    }
+    let free_cells = taken.iter().filter(|&&t| !t).count();
+    assert!(n <= free_cells, "more creatures than free cells on the board");
+
+    let mut positions = Vec::with_capacity(n);
+    let mut tries = 0;
+    while positions.len() < n {
+        let pos = if tries < SPAWN_TRIES {
+            spawn.sample(world_width, world_height, rng)
+        } else {
+            SpawnPattern::Uniform.sample(world_width, world_height, rng)
+        };
+        tries += 1;
+        if pos.x < 0 || pos.y < 0 || pos.x >= world_width || pos.y >= world_height {
+            continue;
+        }
+        let index = (pos.x + world_width * pos.y) as usize;
+        if !taken[index] {
+            taken[index] = true;
+            positions.push(pos);
+            tries = 0;
+        }
+    }
+    positions
 }
 
-fn make_positions<R: Rng>(n: usize, world_width: i32, world_height: i32, rng: &mut R) -> Vec<Point> {
-    (0..n).map(|_| Point::new(
-                rng.gen_range(0..world_width),
-                rng.gen_range(0..world_height)
-            )).collect()
+pub fn make_phases<R: Rng>(n: usize, rng: &mut R) -> Vec<f32> {
+    (0..n).map(|_| rng.gen_range(0.0..TAU)).collect()
 }
 
-impl<N: Nucl> Creatures<N> {
+/// one random number generator per creature, seeded from `master_seed` and its index
+pub fn make_rngs(n: usize, master_seed: u64) -> Vec<StdRng> {
+    (0..n)
+        .map(|index| StdRng::seed_from_u64(stream_seed(master_seed, index as u64)))
+        .collect()
+}
+
+/// keeps the items whose entry in `alive` is `true`
+fn retain_alive<T>(items: &mut Vec<T>, alive: &[bool]) {
+    let mut alive = alive.iter();
+    items.retain(|_| *alive.next().unwrap_or(&true));
+}
+
+impl<N: NeuronNucl> Creatures<N> {
     /// Creates a new `Creatures` object.
     /// Inputs:
     ///
     /// * `n_genoms` - number of individuums (= number of genoms)
-    /// * `n_neurons` - number of neurons per brain
+    /// * `genome_length` - number of nucleotides per genom, i.e. the number of synapses per brain
+    /// * `n_internal_neurons` - number of internal (hidden) neurons per brain
     /// * `mutation_coeff` - controls the mutation rate.
-    ///     `1/mutation_coeff` change of a mutation happening
-    pub fn new<R: Rng>(n_genoms: usize, n_neurons: usize, mutation_coeff: usize, world_height: i32, world_width: i32, rng: &mut R) -> Self {
+    ///   each nucleotide of a child mutates with a chance of `1/mutation_coeff`
+    /// * `world_width`, `world_height` - dimensions of the board the creatures are placed on,
+    ///   every position satisfies `x < world_width` and `y < world_height`
+    /// * `spawn` - where on the board the creatures are placed
+    #[allow(clippy::too_many_arguments)]
+    pub fn new<R: Rng>(
+        n_genoms: usize,
+        genome_length: usize,
+        n_internal_neurons: usize,
+        mutation_coeff: usize,
+        world_width: i32,
+        world_height: i32,
+        spawn: SpawnPattern,
+        rng: &mut R,
+    ) -> Self {
+        let population = Population::random(n_genoms, genome_length, mutation_coeff, rng);
+        Self::from_population(
+            population,
+            n_internal_neurons,
+            world_width,
+            world_height,
+            spawn,
+            rng,
+        )
+    }
 
+    /// Like [`Creatures::new`], but the creatures get the genoms of an existing `population`
+    /// (e.g. one loaded from a file) instead of random ones.
+    pub fn from_population<R: Rng>(
+        population: Population<N, N::Scorer>,
+        n_internal_neurons: usize,
+        world_width: i32,
+        world_height: i32,
+        spawn: SpawnPattern,
+        rng: &mut R,
+    ) -> Self {
+        let n_genoms = population.len();
         Self {
-            genoms: (0..n_genoms).map(|_| Genom::random(n_neurons, rng)).collect(),
-            positions: make_positions(n_genoms,world_width, world_height, rng),
-            mutation_coeff
+            population,
+            positions: make_positions(n_genoms, world_width, world_height, &[], &spawn, rng),
+            phases: make_phases(n_genoms, rng),
+            rngs: make_rngs(n_genoms, rng.gen()),
+            headings: vec![(0, 0); n_genoms],
+            facing: vec![Direction::default(); n_genoms],
+            food_eaten: vec![0; n_genoms],
+            ages: vec![0; n_genoms],
+            max_age: None,
+            energy: vec![i32::MAX; n_genoms],
+            max_energy: None,
+            n_internal_neurons,
+            sensor_range: DEFAULT_SENSOR_RANGE,
+            activation: Activation::default(),
+            arbitration: Arbitration::default(),
+            food_threshold: None,
+            predators: None,
+            spawn,
+            scratch: N::Scratch::default(),
+            watched: None,
+            activations: Vec::new(),
+            timings: PhaseTimings::default(),
+        }
+    }
+
+    /// Ages every creature by one step and removes those older than `max_age`, keeping all the
+    /// per-creature vectors index-aligned and the occupancy grid of the `board` up to date.
+    /// Returns the number of creatures that died.
+    pub fn age(&mut self, board: &mut Board) -> usize {
+        for age in self.ages.iter_mut() {
+            *age += 1;
+        }
+        let max_age = match self.max_age {
+            Some(max_age) => max_age,
+            None => return 0,
+        };
+
+        let alive: Vec<_> = self.ages.iter().map(|&age| age <= max_age).collect();
+        self.retain(board, |i| alive[i])
+    }
+
+    /// Keeps only the creatures for whose index `keep` returns `true`. All per-creature vectors
+    /// are filtered together so they stay index-aligned, and the occupancy grid of the `board` is
+    /// brought up to date. Every removal of creatures has to go through here.
+    /// Returns the number of removed creatures.
+    pub fn retain(&mut self, board: &mut Board, keep: impl FnMut(usize) -> bool) -> usize {
+        let alive: Vec<_> = (0..self.positions.len()).map(keep).collect();
+        let n_dead = alive.iter().filter(|&&alive| !alive).count();
+        if n_dead == 0 {
+            return 0;
+        }
+
+        retain_alive(&mut self.population.genoms, &alive);
+        retain_alive(&mut self.population.species, &alive);
+        retain_alive(&mut self.population.traits, &alive);
+        retain_alive(&mut self.positions, &alive);
+        retain_alive(&mut self.phases, &alive);
+        retain_alive(&mut self.rngs, &alive);
+        retain_alive(&mut self.headings, &alive);
+        retain_alive(&mut self.facing, &alive);
+        retain_alive(&mut self.food_eaten, &alive);
+        retain_alive(&mut self.ages, &alive);
+        retain_alive(&mut self.energy, &alive);
+
+        // the indices have shifted
+        board.fill(&self.positions);
+        self.watched = self
+            .watched
+            .filter(|&i| alive.get(i).copied().unwrap_or(false))
+            .map(|i| alive[..i].iter().filter(|&&alive| alive).count());
+        n_dead
+    }
+
+    /// `true` if creature `i` belongs to the predator species
+    pub fn is_predator(&self, i: usize) -> bool {
+        self.predators.is_some() && self.predators == self.population.species.get(i).copied()
+    }
+
+    /// the energy every creature starts a generation with
+    pub fn full_energy(&self) -> i32 {
+        self.max_energy.unwrap_or(i32::MAX)
+    }
+
+    /// the genoms of the creatures
+    pub fn genoms(&self) -> &[Genom<N, N::Scorer>] {
+        &self.population.genoms
+    }
+
+    /// the heritable traits of the creatures, see [`Traits`]
+    pub fn traits(&self) -> &[Traits] {
+        &self.population.traits
+    }
+
+    /// Creates creatures from existing genoms, placed at the given positions.
+    /// All oscillators start in phase and the breeding parameters are left at their defaults.
+    pub fn from_genoms(genoms: Vec<Genom<N, N::Scorer>>, positions: Vec<Point>) -> Self {
+        assert_eq!(genoms.len(), positions.len(), "every genom needs a position");
+        Self {
+            phases: vec![0.0; genoms.len()],
+            rngs: make_rngs(genoms.len(), 0),
+            headings: vec![(0, 0); genoms.len()],
+            facing: vec![Direction::default(); genoms.len()],
+            food_eaten: vec![0; genoms.len()],
+            ages: vec![0; genoms.len()],
+            max_age: None,
+            energy: vec![i32::MAX; genoms.len()],
+            max_energy: None,
+            population: Population::new(genoms, 1),
+            positions,
+            n_internal_neurons: 0,
+            sensor_range: DEFAULT_SENSOR_RANGE,
+            activation: Activation::default(),
+            arbitration: Arbitration::default(),
+            food_threshold: None,
+            predators: None,
+            spawn: SpawnPattern::default(),
+            scratch: N::Scratch::default(),
+            watched: None,
+            activations: Vec::new(),
+            timings: PhaseTimings::default(),
         }
     }
 }
 
-pub trait NeuronNucl: Nucl {
-    fn simulate<R: Rng>(creatures: &mut Creatures<Self>, rng: &mut R, width: i32, height: i32);
+impl<N: NeuronNucl> Genom<N, N::Scorer> {
+    /// the decoded synapse of every nucleotide in a brain with `n_internal` internal neurons, in
+    /// order
+    pub fn iter_connections(&self, n_internal: usize) -> impl Iterator<Item = Synapse> + '_ {
+        self.nucleotides.iter().map(move |n| n.synapse(n_internal))
+    }
 
-    fn simulate_end<R: Rng>(creatures: &mut Creatures<Self>, rng: &mut R, width: i32, height: i32);
+    /// Rewires nucleotide `index` to encode a synapse from input `input` to output `output`, see
+    /// [`NeuronNucl::with_synapse`]. The genom has to be scored again. Does nothing if `index` is
+    /// out of range.
+    pub fn set_synapse(&mut self, index: usize, input: usize, output: usize, weight: f32) {
+        if let Some(nucl) = self.nucleotides.get_mut(index) {
+            *nucl = nucl.with_synapse(input, output, weight);
+            self.invalidate_score();
+        }
+    }
+
+    /// Number of bits in which the two genoms differ. Nucleotides are compared position by
+    /// position, the tail of the longer genom counts as completely different
+    /// ([`NeuronNucl::BITS`] per nucleotide).
+    pub fn hamming_distance(a: &Self, b: &Self) -> u32 {
+        let common: u32 = a
+            .nucleotides
+            .iter()
+            .zip(b.nucleotides.iter())
+            .map(|(x, y)| x.bit_distance(y))
+            .sum();
+        let tail = a.nucleotides.len().abs_diff(b.nucleotides.len()) as u32;
+        common + tail * N::BITS
+    }
 
-    fn end_generation<R: Rng>(creatures: &mut Creatures<Self>, rng: &mut R, width: i32, height: i32) {
-        let n = creatures.genoms.len();
-        Self::simulate_end(creatures, rng, width, height);
-        let mutation_n = rng.gen_range(0..creatures.mutation_coeff);
-        if mutation_n < n {
-            creatures.genoms[mutation_n].mutate(rng);
+    /// the fraction of bits the two genoms have in common, `1.0` for identical genoms (and for two
+    /// empty ones), see [`Genom::hamming_distance`]
+    pub fn similarity(a: &Self, b: &Self) -> f32 {
+        let bits = a.nucleotides.len().max(b.nucleotides.len()) as u32 * N::BITS;
+        if bits == 0 {
+            return 1.0;
         }
+        1.0 - Genom::hamming_distance(a, b) as f32 / bits as f32
+    }
+
+    /// Removes the nucleotides that can't affect the behaviour: those whose synapse has no weight
+    /// or isn't part of a path from an input to an acting output (see
+    /// [`NeuronNucl::acting_outputs`]). Returns the number of removed nucleotides.
+    pub fn prune_dead_connections(&mut self, n_internal_neurons: usize) -> usize {
+        let synapses: Vec<_> = self.iter_connections(n_internal_neurons).collect();
+        let live = live_synapses(&synapses, n_internal_neurons, &N::acting_outputs());
 
-        creatures.positions = make_positions(n, width, height, rng);
+        let before = self.nucleotides.len();
+        let mut keep = synapses.iter().zip(live).map(|(s, live)| live && s.weight != 0.0);
+        self.nucleotides.retain(|_| keep.next().unwrap_or(true));
+        before - self.nucleotides.len()
+    }
+}
+
+/// Summary of a finished generation, taken right before the next generation replaces it
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct GenerationStats {
+    /// number of creatures that ended the generation inside the selection zone
+    pub survivors: usize,
+    pub best_score: f32,
+    pub mean_score: f32,
+    /// see [`diversity`]
+    pub diversity: f32,
+}
+
+/// Time spent in each phase of a generation, summed over all of its steps
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PhaseTimings {
+    /// the creatures deciding what to do
+    pub decide: Duration,
+    /// carrying out the decisions on the board
+    pub apply: Duration,
+    /// scoring, selecting and breeding the next generation
+    pub end_generation: Duration,
+}
+
+/// number of genoms compared by [`diversity`] in a generation
+pub const DIVERSITY_SAMPLE: usize = 64;
+
+/// The genetic diversity of a population: the mean number of differing bits per nucleotide
+/// between two genoms, averaged over all pairs of a random sample of `sample` genoms.
+/// Identical genoms give `0.0`.
+pub fn diversity<N: NeuronNucl, R: Rng>(
+    genoms: &[Genom<N, N::Scorer>],
+    sample: usize,
+    rng: &mut R,
+) -> f32 {
+    let sample = rand::seq::index::sample(rng, genoms.len(), sample.min(genoms.len()));
+    let sample: Vec<_> = sample.iter().map(|i| &genoms[i]).collect();
+
+    let mut total = 0;
+    let mut n_nucleotides = 0;
+    for (i, a) in sample.iter().enumerate() {
+        for b in sample[i + 1..].iter() {
+            total += Genom::hamming_distance(a, b);
+            n_nucleotides += a.nucleotides.len().max(b.nucleotides.len());
+        }
+    }
+    if n_nucleotides == 0 {
+        return 0.0;
+    }
+    total as f32 / n_nucleotides as f32
+}
+
+/// Groups the genoms into clusters of similar ones and returns the cluster id of each genom, ids
+/// count up from `0` in order of first appearance.
+///
+/// Greedy leader clustering: each genom joins the first cluster whose leader (its first genom) is
+/// at least `threshold` similar to it (see [`Genom::similarity`]), or leads a new cluster.
+pub fn cluster_by_similarity<N: NeuronNucl>(
+    genoms: &[Genom<N, N::Scorer>],
+    threshold: f32,
+) -> Vec<usize> {
+    let mut leaders: Vec<&Genom<N, N::Scorer>> = Vec::new();
+    genoms
+        .iter()
+        .map(|genom| {
+            let cluster =
+                leaders.iter().position(|leader| Genom::similarity(leader, genom) >= threshold);
+            cluster.unwrap_or_else(|| {
+                leaders.push(genom);
+                leaders.len() - 1
+            })
+        })
+        .collect()
+}
+
+pub trait NeuronNucl: Nucl + Hash {
+    type Scorer: Scorer + Clone + From<f32>;
+
+    /// buffers `simulate` keeps in [`Creatures::scratch`] to reuse them from step to step
+    type Scratch: Default;
+
+    /// the synapse this nucleotide encodes in a brain with `n_internal` internal neurons
+    fn synapse(&self, n_internal: usize) -> Synapse;
+
+    /// synapse weights lie in `[-MAX_WEIGHT, MAX_WEIGHT]`
+    const MAX_WEIGHT: f32;
+
+    /// a nucleotide encoding a synapse from input `input` to output `output` (indices into
+    /// [`NeuronNucl::input_names`] and [`NeuronNucl::output_names`]), keeping whatever else
+    /// `self` encodes
+    fn with_synapse(&self, input: usize, output: usize, weight: f32) -> Self;
+
+    /// indices of the output neurons that influence the behaviour, synapses which can't reach
+    /// any of them are dead
+    fn acting_outputs() -> Vec<usize>;
+
+    /// number of bits of the encoding of a nucleotide
+    const BITS: u32;
+
+    /// number of bits in which the encodings of `self` and `other` differ
+    fn bit_distance(&self, other: &Self) -> u32;
+
+    /// names of the input neurons, in the order of their [`Neuron::Input`](crate::brain::Neuron)
+    /// indices
+    fn input_names() -> Vec<String>;
+
+    /// names of the output neurons, in the order of their [`Neuron::Output`](crate::brain::Neuron)
+    /// indices
+    fn output_names() -> Vec<String>;
+
+    /// simulates step number `step` of the generation. Creatures may only move onto free cells of
+    /// the `board`, whose occupancy grid has to be kept up to date.
+    fn simulate<R: Rng>(creatures: &mut Creatures<Self>, rng: &mut R, board: &mut Board, step: u32);
+
+    /// scores every genom according to where its creature ended up relative to `zone`
+    fn score(creatures: &mut Creatures<Self>, board: &Board, zone: &dyn SelectionZone);
+
+    /// builds the next generation from the scored genoms of the current one.
+    /// Returns the number of elites, which have been copied unchanged to the front of the new
+    /// generation and are exempt from mutation.
+    fn simulate_end<R: Rng>(
+        creatures: &mut Creatures<Self>,
+        rng: &mut R,
+        board: &Board,
+        zone: &dyn SelectionZone,
+    ) -> usize;
+
+    /// scores the generation and replaces it by the next one. If a `simu` is given, it scores each
+    /// genom on its own, otherwise the `objective` scores the creatures. Without either
+    /// [`NeuronNucl::score`] does.
+    fn end_generation<R: Rng>(
+        creatures: &mut Creatures<Self>,
+        rng: &mut R,
+        board: &mut Board,
+        zone: &dyn SelectionZone,
+        simu: Option<&dyn Simu<Self, Self::Scorer>>,
+        objective: Option<&dyn Objective<Self>>,
+    ) -> GenerationStats {
+        match (simu, objective) {
+            (Some(simu), _) => {
+                // genoms still holding a score are unchanged elites, see `keep_elite_scores`
+                for genom in creatures.population.genoms.iter_mut().filter(|g| !g.has_score()) {
+                    simu.simulate(genom);
+                }
+            }
+            (None, Some(objective)) => {
+                let scores: Vec<_> = (0..creatures.population.len())
+                    .map(|i| objective.evaluate(i, creatures, board))
+                    .collect();
+                for (genom, score) in creatures.population.genoms.iter_mut().zip(scores) {
+                    genom.set_score(score.into());
+                }
+            }
+            (None, None) => Self::score(creatures, board, zone),
+        }
+        if creatures.predators.is_some() {
+            for i in 0..creatures.population.len() {
+                if creatures.is_predator(i) {
+                    let caught = creatures.food_eaten[i] as f32;
+                    creatures.population.genoms[i].set_score(caught.into());
+                }
+            }
+        }
+        let population = &creatures.population;
+        let stats = GenerationStats {
+            survivors: creatures
+                .positions
+                .iter()
+                .filter(|pos| zone.contains(pos, board.width, board.height))
+                .count(),
+            best_score: population.best_score(),
+            mean_score: population.mean_score(),
+            diversity: diversity(&population.genoms, DIVERSITY_SAMPLE, rng),
+        };
+
+        let n_elite = Self::simulate_end(creatures, rng, board, zone);
+        creatures.population.mutate(n_elite, rng);
+
+        // creatures that died of age are replaced by the next generation
+        let n = creatures.population.len();
+        debug_assert!(n == creatures.population.size || n == 0);
+        creatures.positions =
+            make_positions(n, board.width, board.height, board.obstacles(), &creatures.spawn, rng);
+        board.fill(&creatures.positions);
+        board.scatter_food(rng);
+        board.clear_pheromone();
+        creatures.phases = make_phases(n, rng);
+        creatures.rngs = make_rngs(n, rng.gen());
+        creatures.headings = vec![(0, 0); n];
+        creatures.facing = vec![Direction::default(); n];
+        creatures.food_eaten = vec![0; n];
+        creatures.ages = vec![0; n];
+        creatures.energy = vec![creatures.full_energy(); n];
+
+        stats
     }
 }
 
@@ -76,3 +701,186 @@ impl Scorer for NullScorer {
         0.0
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::andis::AndiN;
+
+    #[test]
+    fn creatures_get_genoms_of_the_genome_length() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let creatures: Creatures<AndiN> =
+            Creatures::new(20, 32, 3, 100, 64, 64, SpawnPattern::default(), &mut rng);
+
+        assert_eq!(creatures.genoms().len(), 20);
+        assert!(creatures.genoms().iter().all(|genom| genom.nucleotides.len() == 32));
+        assert_eq!(creatures.n_internal_neurons, 3);
+    }
+
+    #[test]
+    fn retain_follows_the_watched_creature() {
+        let genoms = (0..4).map(|_| Genom::from_iter(Vec::<AndiN>::new())).collect();
+        let positions = (0..4).map(|x| Point2::new(x, 0)).collect();
+        let mut creatures: Creatures<AndiN> = Creatures::from_genoms(genoms, positions);
+        let mut board = Board::new(8, 8);
+        board.fill(&creatures.positions);
+        creatures.watched = Some(2);
+
+        assert_eq!(creatures.retain(&mut board, |i| i != 0), 1);
+        assert_eq!(creatures.watched, Some(1));
+        assert_eq!(creatures.positions[1].x, 2);
+
+        assert_eq!(creatures.retain(&mut board, |i| i != 1), 1);
+        assert_eq!(creatures.watched, None);
+    }
+
+    #[test]
+    fn diversity_tells_identical_from_random_genoms() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let random: Vec<Genom<AndiN, _>> = (0..50).map(|_| Genom::random(16, &mut rng)).collect();
+        let identical = vec![random[0].clone(); 50];
+
+        assert_eq!(diversity(&identical, DIVERSITY_SAMPLE, &mut rng), 0.0);
+        // two random nucleotides differ in half of their 32 bits
+        let random_diversity = diversity(&random, DIVERSITY_SAMPLE, &mut rng);
+        assert!((14.0..18.0).contains(&random_diversity), "{}", random_diversity);
+    }
+
+    #[test]
+    fn creatures_with_a_max_age_of_3_die_on_step_4() {
+        let genoms = (0..3).map(|_| Genom::from_iter(Vec::<AndiN>::new())).collect();
+        let positions = (0..3).map(|x| Point2::new(x, 0)).collect();
+        let mut creatures: Creatures<AndiN> = Creatures::from_genoms(genoms, positions);
+        let mut board = Board::new(8, 8);
+        board.fill(&creatures.positions);
+        creatures.max_age = Some(3);
+        // the middle one is a step older than the others
+        creatures.ages[1] = 1;
+
+        for _ in 0..2 {
+            assert_eq!(creatures.age(&mut board), 0);
+        }
+        assert_eq!(creatures.age(&mut board), 1);
+        assert_eq!(creatures.ages, vec![3, 3]);
+        assert_eq!(creatures.positions[1].x, 2);
+        assert_eq!(board.occupant(2, 0), Some(1));
+
+        assert_eq!(creatures.age(&mut board), 2);
+        assert!(creatures.positions.is_empty());
+        assert!(creatures.genoms().is_empty());
+    }
+
+    #[test]
+    fn hamming_distance_counts_the_differing_bits() {
+        let mut rng = StdRng::seed_from_u64(2);
+        let a: Genom<AndiN, _> = Genom::random(8, &mut rng);
+        assert_eq!(Genom::hamming_distance(&a, &a.clone()), 0);
+
+        let mut b = a.clone();
+        b.nucleotides[5].mutate_bit(17);
+        assert_eq!(Genom::hamming_distance(&a, &b), 1);
+        assert_eq!(Genom::hamming_distance(&b, &a), 1);
+
+        // the missing tail counts as completely different
+        b.nucleotides.truncate(6);
+        assert_eq!(Genom::hamming_distance(&a, &b), 1 + 2 * AndiN::BITS);
+    }
+
+    #[test]
+    fn spawn_patterns_place_the_creatures() {
+        let mut rng = StdRng::seed_from_u64(6);
+        let obstacles = [Point2::new(0, 5)];
+        let column = make_positions(20, 32, 32, &obstacles, &SpawnPattern::Column(0), &mut rng);
+        assert_eq!(column.len(), 20);
+        assert!(column.iter().all(|pos| pos.x == 0 && pos.y != 5));
+        let mut rows: Vec<_> = column.iter().map(|pos| pos.y).collect();
+        rows.sort_unstable();
+        rows.dedup();
+        assert_eq!(rows.len(), 20);
+
+        let blob = SpawnPattern::Blob {
+            center: (40, 20),
+            sigma: 3.0,
+        };
+        let blob = make_positions(100, 64, 64, &[], &blob, &mut rng);
+        let mean = |coordinate: fn(&Point2) -> i32| {
+            blob.iter().map(coordinate).sum::<i32>() as f32 / blob.len() as f32
+        };
+        assert!((mean(|pos| pos.x) - 40.0).abs() < 1.0, "mean x {}", mean(|pos| pos.x));
+        assert!((mean(|pos| pos.y) - 20.0).abs() < 1.0, "mean y {}", mean(|pos| pos.y));
+    }
+
+    #[test]
+    fn two_well_separated_groups_make_two_clusters() {
+        let mut rng = StdRng::seed_from_u64(4);
+        let leaders: [Genom<AndiN, _>; 2] =
+            [Genom::random(16, &mut rng), Genom::random(16, &mut rng)];
+        assert!(Genom::similarity(&leaders[0], &leaders[1]) < 0.75);
+        // alternating members of the two groups, each a leader with a couple of bits flipped
+        let genoms: Vec<_> = (0..10)
+            .map(|i| {
+                let mut genom = leaders[i % 2].clone();
+                genom.mutate(&mut rng);
+                genom.mutate(&mut rng);
+                genom
+            })
+            .collect();
+
+        let clusters = cluster_by_similarity(&genoms, 0.9);
+        assert_eq!(clusters, [0, 1, 0, 1, 0, 1, 0, 1, 0, 1]);
+    }
+
+    #[test]
+    fn editing_a_synapse_rewires_its_output() {
+        use crate::andis::{AndiS, InputNeurons, OutputNeurons};
+
+        let nucl = AndiN::encode(InputNeurons::PL, OutputNeurons::MvE, 1.0);
+        let mut genom: Genom<AndiN, AndiS> = Genom::from_iter([nucl.clone(), nucl]);
+        genom.set_score(AndiS::new(1.0));
+
+        genom.set_synapse(1, InputNeurons::PL as usize, OutputNeurons::TurnLeft as usize, 1.0);
+        assert!(matches!(genom.nucleotides[0].output(), OutputNeurons::MvE));
+        assert!(matches!(genom.nucleotides[1].output(), OutputNeurons::TurnLeft));
+        assert!(matches!(genom.nucleotides[1].input(), InputNeurons::PL));
+        assert_eq!(genom.nucleotides[1].weight(), genom.nucleotides[0].weight());
+        assert!(!genom.has_score());
+    }
+
+    #[test]
+    fn retain_keeps_the_aux_data_with_its_genom() {
+        use crate::andis::AndiS;
+
+        let genoms = (0..6)
+            .map(|i| {
+                let mut genom = Genom::from_iter(Vec::<AndiN>::new());
+                genom.set_score(AndiS::new(i as f32));
+                genom
+            })
+            .collect();
+        let positions = (0..6).map(|x| Point2::new(x, 0)).collect();
+        let mut creatures: Creatures<AndiN> = Creatures::from_genoms(genoms, positions);
+        // every aux value of creature `i` is derived from `i`
+        for i in 0..6 {
+            creatures.phases[i] = i as f32;
+            creatures.food_eaten[i] = i as u32;
+            creatures.ages[i] = 10 * i as u32;
+            creatures.energy[i] = 100 * i as i32;
+        }
+        let mut board = Board::new(8, 8);
+        board.fill(&creatures.positions);
+
+        assert_eq!(creatures.retain(&mut board, |i| i % 3 != 1), 2);
+        assert_eq!(creatures.positions.len(), 4);
+        for (j, genom) in creatures.genoms().iter().enumerate() {
+            let i = genom.score() as usize;
+            assert_ne!(i % 3, 1);
+            assert_eq!(creatures.positions[j].x, i as i32);
+            assert_eq!(creatures.phases[j], i as f32);
+            assert_eq!(creatures.food_eaten[j], i as u32);
+            assert_eq!(creatures.ages[j], 10 * i as u32);
+            assert_eq!(creatures.energy[j], 100 * i as i32);
+            assert_eq!(board.occupant(i as i32, 0), Some(j));
+        }
+    }
+}
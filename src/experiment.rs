@@ -0,0 +1,154 @@
+use crate::{andis::AndiN, config::SimConfig, creature::GenerationStats, world::World};
+
+/// seed of the sweeps whose base config doesn't have one, so that they are reproducible anyway
+const DEFAULT_SWEEP_SEED: u64 = 0;
+
+// -------------------------------------------------------------------------------------------------
+// --- Parameter Sweeps ----------------------------------------------------------------------------
+// -------------------------------------------------------------------------------------------------
+
+/// one value of the [`SimConfig`] parameter a sweep varies
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SweepParam {
+    MutationCoeff(usize),
+    GenomeLength(usize),
+    InternalNeurons(usize),
+    Creatures(usize),
+    StepsInGeneration(u32),
+}
+
+impl SweepParam {
+    /// Parses a sweep given as `name=value,value,...`, the name being one of `mutation_coeff`,
+    /// `genome_length`, `internal_neurons`, `creatures` and `steps_in_generation`.
+    pub fn parse_sweep(spec: &str) -> Result<Vec<SweepParam>, String> {
+        let (name, values) = spec
+            .split_once('=')
+            .ok_or_else(|| format!("expected `name=value,...`, got `{}`", spec))?;
+        let param: fn(usize) -> SweepParam = match name.trim() {
+            "mutation_coeff" => SweepParam::MutationCoeff,
+            "genome_length" => SweepParam::GenomeLength,
+            "internal_neurons" => SweepParam::InternalNeurons,
+            "creatures" => SweepParam::Creatures,
+            "steps_in_generation" => |value| SweepParam::StepsInGeneration(value as u32),
+            other => return Err(format!("unknown sweep parameter `{}`", other)),
+        };
+        values
+            .split(',')
+            .map(|value| {
+                let value = value.trim();
+                value.parse().map(param).map_err(|_| format!("invalid value `{}`", value))
+            })
+            .collect()
+    }
+
+    /// sets the parameter in `config`
+    pub fn apply(&self, config: &mut SimConfig) {
+        match *self {
+            SweepParam::MutationCoeff(value) => config.mutation_coeff = value,
+            SweepParam::GenomeLength(value) => config.genome_length = value,
+            SweepParam::InternalNeurons(value) => config.internal_neurons = value,
+            SweepParam::Creatures(value) => config.creatures = value,
+            SweepParam::StepsInGeneration(value) => config.steps_in_generation = value,
+        }
+    }
+}
+
+/// the outcome of one run of a sweep
+#[derive(Debug, Clone)]
+pub struct SweepResult {
+    /// the parameter value of this run
+    pub param: SweepParam,
+    /// the full config of this run, the base config with `param` applied
+    pub config: SimConfig,
+    /// the number of generations that have run
+    pub generations: u32,
+    /// the stats of the last generation
+    pub final_stats: GenerationStats,
+}
+
+/// Runs `generations` generations headlessly for each of the `param_values`, each one applied to
+/// its own copy of `base_config`. All runs use the seed of the base config (or a fixed one), so
+/// the results only differ by the parameter and are the same every time.
+pub fn run_sweep(
+    param_values: &[SweepParam],
+    generations: u32,
+    base_config: &SimConfig,
+) -> Vec<SweepResult> {
+    param_values
+        .iter()
+        .map(|&param| {
+            let mut config = base_config.clone();
+            config.seed = Some(config.seed.unwrap_or(DEFAULT_SWEEP_SEED));
+            param.apply(&mut config);
+
+            let mut world: World<_, AndiN> = config.builder().build();
+            let stats = world.run_headless(generations);
+            SweepResult {
+                param,
+                config,
+                generations: stats.len() as u32,
+                final_stats: stats.last().copied().unwrap_or_default(),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// a small world, so the sweeps run quickly
+    fn small_config() -> SimConfig {
+        SimConfig {
+            creatures: 40,
+            genome_length: 4,
+            width: 32,
+            height: 32,
+            steps_in_generation: 20,
+            ..SimConfig::default()
+        }
+    }
+
+    #[test]
+    fn parse_sweep_reads_the_values() {
+        assert_eq!(
+            SweepParam::parse_sweep("mutation_coeff=10, 100"),
+            Ok(vec![SweepParam::MutationCoeff(10), SweepParam::MutationCoeff(100)])
+        );
+        assert_eq!(
+            SweepParam::parse_sweep("steps_in_generation=5"),
+            Ok(vec![SweepParam::StepsInGeneration(5)])
+        );
+        assert!(SweepParam::parse_sweep("mutation_coeff").is_err());
+        assert!(SweepParam::parse_sweep("colour=1").is_err());
+        assert!(SweepParam::parse_sweep("creatures=1,many").is_err());
+    }
+
+    #[test]
+    fn sweep_echoes_the_config_of_each_run() {
+        let params = [SweepParam::MutationCoeff(10), SweepParam::MutationCoeff(1000)];
+        let results = run_sweep(&params, 2, &small_config());
+
+        assert_eq!(results.len(), 2);
+        for (result, (param, mutation_coeff)) in results.iter().zip(params.iter().zip([10, 1000])) {
+            assert_eq!(result.param, *param);
+            assert_eq!(result.config.mutation_coeff, mutation_coeff);
+            assert_eq!(result.config.creatures, 40);
+            assert_eq!(result.config.seed, Some(DEFAULT_SWEEP_SEED));
+            assert_eq!(result.generations, 2);
+        }
+    }
+
+    #[test]
+    fn sweeps_are_reproducible() {
+        let params = [SweepParam::GenomeLength(2), SweepParam::GenomeLength(8)];
+        let config = small_config();
+        let first = run_sweep(&params, 2, &config);
+        let second = run_sweep(&params, 2, &config);
+
+        let stats = |results: &[SweepResult]| -> Vec<_> {
+            results.iter().map(|result| result.final_stats).collect()
+        };
+        assert_eq!(stats(&first), stats(&second));
+    }
+}
@@ -1,5 +1,14 @@
-use rand::Rng;
-use std::fmt::{Debug, Display, Formatter};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::{
+    cmp::{Ordering, Reverse},
+    collections::BinaryHeap,
+    fmt::{Debug, Display, Formatter},
+    thread,
+};
+
+use crate::utils::stream_seed;
 
 // --------------------------------------------------------------------------------------------
 // --- Genetic Algorithm ----------------------------------------------------------------------
@@ -52,13 +61,22 @@ pub trait Scorer {
 /// (from a given set of nucleotides, randomly, empty with capacity, from a previous generation).
 ///
 /// Furthermore it implements the genetic operators, crossover and mutation
+///
+/// With the `serde` feature only the nucleotides are serialized, the scorer is skipped and will be
+/// `None` after deserialization.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(bound(serialize = "N: Serialize", deserialize = "N: Deserialize<'de>"))
+)]
 pub struct Genom<N, S>
 where
     N: Nucl,
     S: Scorer,
 {
     pub nucleotides: Vec<N>,
+    #[cfg_attr(feature = "serde", serde(skip))]
     scorer: Option<S>,
 }
 
@@ -99,6 +117,18 @@ where
         }
     }
 
+    /// like [`Genom::from_previous`] but keeps the score, for copies that won't change (e.g. the
+    /// elites) so they don't have to be scored again
+    pub fn from_previous_with_score(other: &Self) -> Self
+    where
+        S: Clone,
+    {
+        Genom {
+            nucleotides: other.nucleotides.to_vec(),
+            scorer: other.scorer.clone(),
+        }
+    }
+
     /// create a new genom from an iterator over nucleotides
     pub fn from_iter<T>(iterator: T) -> Self
     where
@@ -114,6 +144,10 @@ where
     // ---Genetic operators ------------------------------------------------------------------------
     // ---------------------------------------------------------------------------------------------
 
+    // All crossover operators accept parents of differing lengths. The child is always as long
+    // as the longer parent: where only one parent has a nucleotide, it is copied from that one.
+    // Empty parents give an empty child.
+
     /// mutatic crossover. this is meant to save time and resources of allocation (and hence speedup).
     /// I am not sure if this works
     ///
@@ -122,24 +156,24 @@ where
     where
         R: rand::Rng,
     {
-        let n = rng.gen_range(0..a.nucleotides.len());
-        unsafe {
-            child.nucleotides.set_len(0);
+        let len = a.nucleotides.len().max(b.nucleotides.len());
+        if len <= 1 {
+            // nothing to blend, the child gets the single nucleotide of either parent (if any)
+            let picked = Self::crossover_cut_at(a, b, rng.gen_range(0..=len));
+            child.nucleotides.clone_from(&picked.nucleotides);
+            return;
         }
-        child.nucleotides.extend(
-            a.nucleotides
-                .iter()
-                .zip(b.nucleotides.iter())
-                .take(n)
-                .map(|(first, second)| N::crossover(first, second)),
-        );
-        child.nucleotides.extend(
-            a.nucleotides
-                .iter()
-                .zip(b.nucleotides.iter())
-                .skip(n)
-                .map(|(first, second)| N::crossover(second, first)),
-        );
+        Self::crossover_mut_at(a, b, child, rng.gen_range(0..len));
+    }
+
+    /// [`Genom::crossover_mut`] at the crossover point `n`, see [`Genom::crossover_at`]
+    pub fn crossover_mut_at(a: &Self, b: &Self, child: &mut Self, n: usize) {
+        let len = a.nucleotides.len().max(b.nucleotides.len());
+        let n = n.min(len);
+        // clear() keeps the capacity, so the extends below reuse the child's allocation
+        child.nucleotides.clear();
+        child.nucleotides.extend((0..n).map(|i| blend(&a.nucleotides, &b.nucleotides, i)));
+        child.nucleotides.extend((n..len).map(|i| blend(&b.nucleotides, &a.nucleotides, i)));
     }
 
     /// The crossover genetic operator
@@ -151,22 +185,22 @@ where
     where
         R: rand::Rng,
     {
-        let n = rng.gen_range(0..a.nucleotides.len());
-        let mut nucl: Vec<N> = Vec::with_capacity(a.nucleotides.len());
-        nucl.extend(
-            a.nucleotides
-                .iter()
-                .zip(b.nucleotides.iter())
-                .take(n)
-                .map(|(first, second)| N::crossover(first, second)),
-        );
-        nucl.extend(
-            a.nucleotides
-                .iter()
-                .zip(b.nucleotides.iter())
-                .skip(n)
-                .map(|(first, second)| N::crossover(second, first)),
-        );
+        let len = a.nucleotides.len().max(b.nucleotides.len());
+        if len <= 1 {
+            // nothing to blend, the child gets the single nucleotide of either parent (if any)
+            return Self::crossover_cut_at(a, b, rng.gen_range(0..=len));
+        }
+        Self::crossover_at(a, b, rng.gen_range(0..len))
+    }
+
+    /// [`Genom::crossover`] at the crossover point `n`: the first `n` nucleotides are
+    /// `crossover(A,B)`, the rest `crossover(B,A)`. `n` is clamped to the length of the child.
+    pub fn crossover_at(a: &Self, b: &Self, n: usize) -> Self {
+        let len = a.nucleotides.len().max(b.nucleotides.len());
+        let n = n.min(len);
+        let mut nucl: Vec<N> = Vec::with_capacity(len);
+        nucl.extend((0..n).map(|i| blend(&a.nucleotides, &b.nucleotides, i)));
+        nucl.extend((n..len).map(|i| blend(&b.nucleotides, &a.nucleotides, i)));
         Genom {
             nucleotides: nucl,
             scorer: None,
@@ -179,10 +213,71 @@ where
     where
         R: rand::Rng,
     {
-        let n = rng.gen_range(0..a.nucleotides.len());
-        let mut nucl: Vec<N> = Vec::with_capacity(n);
-        nucl.extend(a.nucleotides.iter().take(n).cloned());
-        nucl.extend(b.nucleotides.iter().skip(n).cloned());
+        let len = a.nucleotides.len().max(b.nucleotides.len());
+        Self::crossover_cut_at(a, b, rng.gen_range(0..len.max(1)))
+    }
+
+    /// [`Genom::crossover_cut`] at the cut point `n`: the first `n` nucleotides come from `a`, the
+    /// rest from `b`. `n` is clamped to the length of the child.
+    pub fn crossover_cut_at(a: &Self, b: &Self, n: usize) -> Self {
+        let len = a.nucleotides.len().max(b.nucleotides.len());
+        let n = n.min(len);
+        let mut nucl: Vec<N> = Vec::with_capacity(len);
+        nucl.extend((0..n).map(|i| pick(&a.nucleotides, &b.nucleotides, i)));
+        nucl.extend((n..len).map(|i| pick(&b.nucleotides, &a.nucleotides, i)));
+        Genom {
+            nucleotides: nucl,
+            scorer: None,
+        }
+    }
+
+    /// two-point crossover, the child is `AABBBBAA`: the middle segment between two randomly
+    /// chosen cut points `p < q` comes from `b`, the rest from `a`.
+    /// If both cut points coincide this degenerates to the single point [`Genom::crossover_cut`].
+    pub fn crossover_two_point<R>(a: &Self, b: &Self, rng: &mut R) -> Self
+    where
+        R: rand::Rng,
+    {
+        let len = a.nucleotides.len().max(b.nucleotides.len());
+        let x = rng.gen_range(0..=len);
+        let y = rng.gen_range(0..=len);
+        Self::crossover_two_point_at(a, b, x, y)
+    }
+
+    /// [`Genom::crossover_two_point`] at the cut points `x` and `y`, in any order. The cut points
+    /// are clamped to the length of the child.
+    pub fn crossover_two_point_at(a: &Self, b: &Self, x: usize, y: usize) -> Self {
+        let len = a.nucleotides.len().max(b.nucleotides.len());
+        let (p, q) = (x.min(y).min(len), x.max(y).min(len));
+        let q = if p == q { len } else { q };
+
+        let mut nucl: Vec<N> = Vec::with_capacity(len);
+        nucl.extend((0..p).map(|i| pick(&a.nucleotides, &b.nucleotides, i)));
+        nucl.extend((p..q).map(|i| pick(&b.nucleotides, &a.nucleotides, i)));
+        nucl.extend((q..len).map(|i| pick(&a.nucleotides, &b.nucleotides, i)));
+        Genom {
+            nucleotides: nucl,
+            scorer: None,
+        }
+    }
+
+    /// uniform crossover, each nucleotide of the child is taken from `a` with probability `ratio`
+    /// and from `b` otherwise. Complements the blending [`Genom::crossover`].
+    pub fn crossover_uniform<R>(a: &Self, b: &Self, ratio: f64, rng: &mut R) -> Self
+    where
+        R: rand::Rng,
+    {
+        let len = a.nucleotides.len().max(b.nucleotides.len());
+        let ratio = ratio.clamp(0.0, 1.0);
+        let nucl: Vec<N> = (0..len)
+            .map(|i| {
+                if rng.gen_bool(ratio) {
+                    pick(&a.nucleotides, &b.nucleotides, i)
+                } else {
+                    pick(&b.nucleotides, &a.nucleotides, i)
+                }
+            })
+            .collect();
         Genom {
             nucleotides: nucl,
             scorer: None,
@@ -190,14 +285,19 @@ where
     }
 
     /// this crossover function takes alternating quarters of both parents
-    /// child = `AABBAABB`
+    /// child = `AABBAABB`. The last quarter takes the remainder if the length isn't divisible by 4.
     pub fn crossover_4th(a: &Self, b: &Self) -> Self {
-        let n = a.nucleotides.len() / 4;
-        let mut nucl: Vec<N> = Vec::with_capacity(n);
-        nucl.extend(a.nucleotides.iter().take(n).cloned());
-        nucl.extend(b.nucleotides.iter().skip(n).take(n).cloned());
-        nucl.extend(a.nucleotides.iter().skip(2 * n).take(n).cloned());
-        nucl.extend(b.nucleotides.iter().skip(3 * n).take(n).cloned());
+        let len = a.nucleotides.len().max(b.nucleotides.len());
+        let n = len / 4;
+        let nucl: Vec<N> = (0..len)
+            .map(|i| {
+                if (i / n.max(1)).min(3).is_multiple_of(2) {
+                    pick(&a.nucleotides, &b.nucleotides, i)
+                } else {
+                    pick(&b.nucleotides, &a.nucleotides, i)
+                }
+            })
+            .collect();
         Genom {
             nucleotides: nucl,
             scorer: None,
@@ -211,23 +311,65 @@ where
     where
         R: rand::Rng,
     {
+        if self.nucleotides.is_empty() {
+            return;
+        }
         let n = rng.gen_range(0..self.nucleotides.len());
-        unsafe {
-            self.nucleotides.get_unchecked_mut(n).mutate(rng);
+        self.mutate_at(n, rng);
+    }
+
+    /// [`Genom::mutate`] of the nucleotide at `index`, does nothing if `index` is out of range
+    pub fn mutate_at<R>(&mut self, index: usize, rng: &mut R)
+    where
+        R: rand::Rng,
+    {
+        if let Some(nucl) = self.nucleotides.get_mut(index) {
+            nucl.mutate(rng);
+            self.invalidate_score();
         }
     }
 
-    pub fn cut(&mut self, len: usize) {
-        let mut new_nucl: Vec<N> = self
-            .nucleotides
-            .iter()
-            .skip(len)
-            .map(|x| x.clone())
-            .collect();
-        for _ in 0..len {
-            new_nucl.push(N::default());
+    /// walks the whole genom and mutates each nucleotide with probability `prob`
+    pub fn mutate_all<R>(&mut self, prob: f64, rng: &mut R)
+    where
+        R: rand::Rng,
+    {
+        for nucl in self.nucleotides.iter_mut() {
+            if rng.gen_bool(prob) {
+                nucl.mutate(rng);
+                self.scorer = None;
+            }
+        }
+    }
+
+    /// structural mutation, inserts a random nucleotide at a random position
+    pub fn mutate_insert<R>(&mut self, rng: &mut R)
+    where
+        R: rand::Rng,
+    {
+        let n = rng.gen_range(0..=self.nucleotides.len());
+        self.nucleotides.insert(n, N::random(rng));
+        self.invalidate_score();
+    }
+
+    /// structural mutation, removes a random nucleotide. A genom is never shortened below a single
+    /// nucleotide.
+    pub fn mutate_delete<R>(&mut self, rng: &mut R)
+    where
+        R: rand::Rng,
+    {
+        if self.nucleotides.len() <= 1 {
+            return;
         }
-        self.nucleotides = new_nucl;
+        let n = rng.gen_range(0..self.nucleotides.len());
+        self.nucleotides.remove(n);
+        self.invalidate_score();
+    }
+
+    /// cuts the genom down to its first `len` nucleotides, the rest is dropped. A genom that is
+    /// already shorter is left as is. Unlike [`Genom::shift`] this changes the length.
+    pub fn cut(&mut self, len: usize) {
+        self.nucleotides.truncate(len);
     }
 
     // ---------------------------------------------------------------------------------------------
@@ -244,13 +386,55 @@ where
         }
     }
 
-    /// for dynamic usage, the foremost element might not be needed anymore, this shifts the nucleotides by 1
+    /// stores the calculated scorer in the genom
+    pub fn set_score(&mut self, scorer: S) {
+        self.scorer = Some(scorer);
+    }
+
+    /// `true` if the genom has been scored since it last changed
+    pub fn has_score(&self) -> bool {
+        self.scorer.is_some()
+    }
+
+    /// forgets the score, to be called whenever the nucleotides change. The mutation operators do
+    /// so themselves.
+    pub fn invalidate_score(&mut self) {
+        self.scorer = None;
+    }
+
+    /// for dynamic usage, the foremost element might not be needed anymore, this rotates the
+    /// nucleotides by 1: the foremost one becomes the last, the length doesn't change
     pub fn shift(&mut self) {
         self.nucleotides.rotate_left(1);
     }
 
 }
 
+impl<N, S> Genom<N, S>
+where
+    N: Nucl + PartialEq,
+    S: Scorer,
+{
+    /// `true` if both genoms carry the same nucleotides, regardless of their scores
+    pub fn same_nucleotides(&self, other: &Self) -> bool {
+        self.nucleotides == other.nucleotides
+    }
+}
+
+/// the nucleotide at `i` of `first`, or of `second` if `first` is too short
+fn pick<N: Nucl>(first: &[N], second: &[N], i: usize) -> N {
+    first.get(i).unwrap_or_else(|| &second[i]).clone()
+}
+
+/// `N::crossover` of the nucleotides at `i`, or a copy of the only one present if a parent is too
+/// short
+fn blend<N: Nucl>(first: &[N], second: &[N], i: usize) -> N {
+    match (first.get(i), second.get(i)) {
+        (Some(x), Some(y)) => N::crossover(x, y),
+        _ => pick(first, second, i),
+    }
+}
+
 // The following is a convenience implementation for a Genom whose nucleotides implement Display
 
 impl<N, S> Display for Genom<N, S>
@@ -283,3 +467,530 @@ S: Scorer
         write!(f, "]")
     }
 }
+
+// --------------------------------------------------------------------------------------------
+// --- Selection ------------------------------------------------------------------------------
+// --------------------------------------------------------------------------------------------
+
+/// How the mating partners are picked from the parents
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum SelectionStrategy {
+    /// every parent mates with a randomly assigned partner, no fitness pressure besides being
+    /// a parent in the first place
+    #[default]
+    RandomPairing,
+    /// each partner is the best scoring of `size` randomly drawn parents
+    Tournament { size: usize },
+    /// each partner is drawn with a probability proportional to its score
+    Roulette,
+}
+
+/// returns the index of the best of `size` randomly drawn `scores` (drawn with replacement).
+/// `scores` must not be empty.
+fn tournament_index<R: Rng>(scores: &[f32], size: usize, rng: &mut R) -> usize {
+    (0..size.max(1))
+        .map(|_| rng.gen_range(0..scores.len()))
+        .max_by(|&a, &b| scores[a].total_cmp(&scores[b]))
+        .unwrap()
+}
+
+/// Draws an index with a probability proportional to its score (roulette wheel selection).
+/// Negative scores count as `0.0`, if all scores are zero the draw is uniform.
+/// `scores` must not be empty.
+pub fn select_roulette<R: Rng>(scores: &[f32], rng: &mut R) -> usize {
+    let total: f32 = scores.iter().map(|s| s.max(0.0)).sum();
+    if !(total > 0.0 && total.is_finite()) {
+        return rng.gen_range(0..scores.len());
+    }
+
+    let mut target = rng.gen_range(0.0..total);
+    for (i, score) in scores.iter().enumerate() {
+        let score = score.max(0.0);
+        if target < score {
+            return i;
+        }
+        target -= score;
+    }
+    // rounding may leave a tiny remainder, it belongs to the last positive score
+    scores.iter().rposition(|&s| s > 0.0).unwrap_or(scores.len() - 1)
+}
+
+/// a score with its index, ordered by score and on ties the lower index first
+#[derive(PartialEq)]
+struct Ranked {
+    score: f32,
+    index: usize,
+}
+
+impl Eq for Ranked {}
+
+impl PartialOrd for Ranked {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Ranked {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.score.total_cmp(&other.score).then(other.index.cmp(&self.index))
+    }
+}
+
+/// Returns the indices of the `k` best `scores`, best first (ties go to the lower index).
+/// Keeps only `k` candidates in a heap, which is `O(n log k)` instead of sorting all scores.
+pub fn select_top_k(scores: &[f32], k: usize) -> Vec<usize> {
+    let k = k.min(scores.len());
+    if k == 0 {
+        return Vec::new();
+    }
+
+    // min-heap, the top is the worst of the best k so far
+    let mut heap = BinaryHeap::with_capacity(k + 1);
+    for (index, &score) in scores.iter().enumerate() {
+        let ranked = Reverse(Ranked { score, index });
+        if heap.len() < k {
+            heap.push(ranked);
+        } else if heap.peek().is_some_and(|worst| ranked < *worst) {
+            heap.pop();
+            heap.push(ranked);
+        }
+    }
+    heap.into_sorted_vec().into_iter().map(|Reverse(r)| r.index).collect()
+}
+
+/// Picks `n` pairs of mating partners, as indices into `parents`, according to `strategy`. Yields
+/// no pairs at all if there are no parents.
+pub fn mating_indices<N, S, R>(
+    parents: &[&Genom<N, S>],
+    n: usize,
+    strategy: SelectionStrategy,
+    rng: &mut R,
+) -> Vec<(usize, usize)>
+where
+    N: Nucl,
+    S: Scorer,
+    R: Rng,
+{
+    let mut pairs = Vec::with_capacity(n);
+    if parents.is_empty() {
+        return pairs;
+    }
+
+    match strategy {
+        SelectionStrategy::RandomPairing => {
+            // shuffle the partners by sorting them by a random key, once per round
+            let mut partners: Vec<_> = (0..parents.len()).map(|i| (0u32, i)).collect();
+            while pairs.len() < n {
+                for (key, _) in partners.iter_mut() {
+                    *key = rng.next_u32();
+                }
+                partners.sort_by_key(|(key, _)| *key);
+
+                for (a, (_, b)) in partners.iter().enumerate().take(n - pairs.len()) {
+                    pairs.push((a, *b));
+                }
+            }
+        }
+        SelectionStrategy::Tournament { size } => {
+            let scores: Vec<_> = parents.iter().map(|p| p.score()).collect();
+            while pairs.len() < n {
+                let a = tournament_index(&scores, size, rng);
+                let b = tournament_index(&scores, size, rng);
+                pairs.push((a, b));
+            }
+        }
+        SelectionStrategy::Roulette => {
+            let scores: Vec<_> = parents.iter().map(|p| p.score()).collect();
+            while pairs.len() < n {
+                let a = select_roulette(&scores, rng);
+                let b = select_roulette(&scores, rng);
+                pairs.push((a, b));
+            }
+        }
+    }
+    pairs
+}
+
+/// two genoms mating, see [`crossover_parallel`]
+pub type MatingPair<'a, N, S> = (&'a Genom<N, S>, &'a Genom<N, S>);
+
+/// The children of the mating `pairs`, one per pair in order, crossed over on `threads` threads.
+/// Each pair gets its own random number generator, seeded from a single draw of `rng` and the
+/// index of the pair, so the children don't depend on the number of threads. With at most one
+/// thread they are crossed over on the current one.
+pub fn crossover_parallel<N, S, R>(
+    pairs: &[MatingPair<N, S>],
+    threads: usize,
+    rng: &mut R,
+) -> Vec<Genom<N, S>>
+where
+    N: Nucl + Send + Sync,
+    S: Scorer + Send + Sync,
+    R: Rng,
+{
+    let master_seed: u64 = rng.gen();
+    // the children of `pairs`, the first one being the pair at `offset`
+    let breed = move |offset: usize, pairs: &[MatingPair<N, S>]| -> Vec<_> {
+        pairs
+            .iter()
+            .enumerate()
+            .map(|(i, (a, b))| Genom::crossover(a, b, &mut pair_rng(master_seed, offset + i)))
+            .collect()
+    };
+    if threads <= 1 {
+        return breed(0, pairs);
+    }
+
+    let chunk_size = pairs.len().div_ceil(threads).max(1);
+    thread::scope(|scope| {
+        let handles: Vec<_> = pairs
+            .chunks(chunk_size)
+            .enumerate()
+            .map(|(chunk, pairs)| scope.spawn(move || breed(chunk * chunk_size, pairs)))
+            .collect();
+        handles
+            .into_iter()
+            .flat_map(|handle| handle.join().expect("a crossover thread panicked"))
+            .collect()
+    })
+}
+
+/// the random number generator of the pair at `index` in [`crossover_parallel`]
+fn pair_rng(master_seed: u64, index: usize) -> StdRng {
+    StdRng::seed_from_u64(stream_seed(master_seed, index as u64))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::andis::{AndiN, AndiS};
+    use proptest::prelude::*;
+    // proptest's prelude has its own `Rng`
+    use rand::Rng;
+
+    type AndiGenom = Genom<AndiN, AndiS>;
+
+    /// genoms of up to 40 random nucleotides, the empty one included
+    fn genom() -> impl Strategy<Value = AndiGenom> {
+        prop::collection::vec(any::<u32>(), 0..40)
+            .prop_map(|encoded| Genom::from_iter(encoded.into_iter().map(AndiN::decode)))
+    }
+
+    /// a random genom of `len` nucleotides, the same one for the same `seed`
+    fn random_genom(len: usize, seed: u64) -> AndiGenom {
+        Genom::random(len, &mut StdRng::seed_from_u64(seed))
+    }
+
+    /// for each nucleotide of the child whether it is the one of `a`
+    fn from_a(child: &AndiGenom, a: &AndiGenom) -> Vec<bool> {
+        child.nucleotides.iter().zip(&a.nucleotides).map(|(x, y)| x == y).collect()
+    }
+
+    /// the child is as long as the longer parent and each of its nucleotides is the one of either
+    /// parent at the same position (`AndiN::crossover` copies the first one)
+    fn traces_to_parents(
+        child: &AndiGenom,
+        a: &AndiGenom,
+        b: &AndiGenom,
+    ) -> Result<(), TestCaseError> {
+        prop_assert_eq!(child.nucleotides.len(), a.nucleotides.len().max(b.nucleotides.len()));
+        for (i, nucl) in child.nucleotides.iter().enumerate() {
+            prop_assert!(
+                a.nucleotides.get(i) == Some(nucl) || b.nucleotides.get(i) == Some(nucl),
+                "nucleotide {} of the child comes from neither parent",
+                i
+            );
+        }
+        Ok(())
+    }
+
+    proptest! {
+        #[test]
+        fn crossover_at_traces_to_parents(a in genom(), b in genom(), n in 0usize..50) {
+            traces_to_parents(&Genom::crossover_at(&a, &b, n), &a, &b)?;
+            let mut child = Genom::with_capacity(0);
+            Genom::crossover_mut_at(&a, &b, &mut child, n);
+            traces_to_parents(&child, &a, &b)?;
+        }
+
+        #[test]
+        fn crossover_cut_at_splits_at_n(a in genom(), b in genom(), n in 0usize..50) {
+            let child = Genom::crossover_cut_at(&a, &b, n);
+            traces_to_parents(&child, &a, &b)?;
+            for (i, nucl) in child.nucleotides.iter().enumerate() {
+                let (first, second) = if i < n { (&a, &b) } else { (&b, &a) };
+                let expected = first.nucleotides.get(i).or(second.nucleotides.get(i));
+                prop_assert_eq!(Some(nucl), expected);
+            }
+        }
+
+        #[test]
+        fn crossover_two_point_at_traces_to_parents(
+            a in genom(),
+            b in genom(),
+            x in 0usize..50,
+            y in 0usize..50,
+        ) {
+            traces_to_parents(&Genom::crossover_two_point_at(&a, &b, x, y), &a, &b)?;
+        }
+
+        #[test]
+        fn crossover_4th_traces_to_parents(a in genom(), b in genom()) {
+            traces_to_parents(&Genom::crossover_4th(&a, &b), &a, &b)?;
+        }
+
+        #[test]
+        fn random_crossovers_trace_to_parents(a in genom(), b in genom(), seed in any::<u64>()) {
+            let mut rng = StdRng::seed_from_u64(seed);
+            traces_to_parents(&Genom::crossover(&a, &b, &mut rng), &a, &b)?;
+            traces_to_parents(&Genom::crossover_cut(&a, &b, &mut rng), &a, &b)?;
+            traces_to_parents(&Genom::crossover_two_point(&a, &b, &mut rng), &a, &b)?;
+            traces_to_parents(&Genom::crossover_uniform(&a, &b, 0.5, &mut rng), &a, &b)?;
+        }
+
+        #[test]
+        fn mutations_keep_the_length_in_bounds(genom in genom(), seed in any::<u64>()) {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let len = genom.nucleotides.len();
+
+            let mut mutated = Genom::from_previous(&genom);
+            mutated.mutate(&mut rng);
+            prop_assert_eq!(mutated.nucleotides.len(), len);
+            let changed = mutated
+                .nucleotides
+                .iter()
+                .zip(&genom.nucleotides)
+                .filter(|(x, y)| x != y)
+                .count();
+            prop_assert_eq!(changed, len.min(1));
+
+            let mut mutated = Genom::from_previous(&genom);
+            mutated.mutate_all(0.5, &mut rng);
+            prop_assert_eq!(mutated.nucleotides.len(), len);
+
+            let mut mutated = Genom::from_previous(&genom);
+            mutated.mutate_insert(&mut rng);
+            prop_assert_eq!(mutated.nucleotides.len(), len + 1);
+
+            let mut mutated = Genom::from_previous(&genom);
+            mutated.mutate_delete(&mut rng);
+            prop_assert_eq!(mutated.nucleotides.len(), if len > 1 { len - 1 } else { len });
+        }
+    }
+
+    #[test]
+    fn crossover_mut_reuses_the_child_and_matches_crossover() {
+        let (a, b) = (random_genom(16, 1), random_genom(16, 2));
+        let mut child = Genom::with_capacity(16);
+        let (mut rng, mut reference_rng) = (StdRng::seed_from_u64(3), StdRng::seed_from_u64(3));
+
+        let mut capacity = child.nucleotides.capacity();
+        for _ in 0..100 {
+            Genom::crossover_mut(&a, &b, &mut child, &mut rng);
+            let expected = Genom::crossover(&a, &b, &mut reference_rng);
+
+            assert!(child.nucleotides.capacity() >= capacity);
+            assert_eq!(child.nucleotides, expected.nucleotides);
+            capacity = child.nucleotides.capacity();
+        }
+    }
+
+    #[test]
+    fn mutate_all_follows_the_probability() {
+        let genom = random_genom(64, 4);
+        let mut rng = StdRng::seed_from_u64(5);
+
+        let mut mutated = Genom::from_previous(&genom);
+        mutated.mutate_all(1.0, &mut rng);
+        assert!(mutated.nucleotides.iter().zip(&genom.nucleotides).all(|(x, y)| x != y));
+
+        let mut unchanged = Genom::from_previous(&genom);
+        unchanged.mutate_all(0.0, &mut rng);
+        assert_eq!(unchanged.nucleotides, genom.nucleotides);
+    }
+
+    #[test]
+    fn crossing_parents_of_differing_lengths_gives_the_longer_length() {
+        let (a, b) = (random_genom(10, 6), random_genom(8, 7));
+        let mut rng = StdRng::seed_from_u64(8);
+
+        for child in [
+            Genom::crossover(&a, &b, &mut rng),
+            Genom::crossover(&b, &a, &mut rng),
+            Genom::crossover_cut(&a, &b, &mut rng),
+            Genom::crossover_two_point(&b, &a, &mut rng),
+            Genom::crossover_uniform(&a, &b, 0.5, &mut rng),
+            Genom::crossover_4th(&b, &a),
+        ] {
+            assert_eq!(child.nucleotides.len(), 10);
+            // the tail only one parent has is copied from it
+            assert_eq!(child.nucleotides[8..], a.nucleotides[8..]);
+        }
+    }
+
+    #[test]
+    fn two_point_crossover_takes_the_middle_from_b() {
+        let (a, b) = (random_genom(8, 9), random_genom(8, 10));
+        let expected = [true, true, false, false, false, true, true, true];
+        assert_eq!(from_a(&Genom::crossover_two_point_at(&a, &b, 2, 5), &a), expected);
+        assert_eq!(from_a(&Genom::crossover_two_point_at(&a, &b, 5, 2), &a), expected);
+
+        // the random cut points are drawn like this
+        let mut rng = StdRng::seed_from_u64(11);
+        let child = Genom::crossover_two_point(&a, &b, &mut rng);
+        let mut rng = StdRng::seed_from_u64(11);
+        let (x, y) = (rng.gen_range(0..=8), rng.gen_range(0..=8));
+        assert_eq!(child.nucleotides, Genom::crossover_two_point_at(&a, &b, x, y).nucleotides);
+    }
+
+    #[test]
+    fn uniform_crossover_mixes_by_the_ratio() {
+        let (a, b) = (random_genom(1000, 12), random_genom(1000, 13));
+        let mut rng = StdRng::seed_from_u64(14);
+
+        let all_a = Genom::crossover_uniform(&a, &b, 1.0, &mut rng);
+        assert_eq!(all_a.nucleotides, a.nucleotides);
+        let all_b = Genom::crossover_uniform(&a, &b, 0.0, &mut rng);
+        assert_eq!(all_b.nucleotides, b.nucleotides);
+
+        let mixed = Genom::crossover_uniform(&a, &b, 0.5, &mut rng);
+        let n_from_a = from_a(&mixed, &a).into_iter().filter(|&from_a| from_a).count();
+        assert!((400..600).contains(&n_from_a), "{} of 1000 from a", n_from_a);
+    }
+
+    /// the indices of the `k` best `scores` by sorting all of them
+    fn top_k_by_sorting(scores: &[f32], k: usize) -> Vec<usize> {
+        let mut indices: Vec<_> = (0..scores.len()).collect();
+        indices.sort_by(|&i, &j| scores[j].total_cmp(&scores[i]));
+        indices.truncate(k);
+        indices
+    }
+
+    proptest! {
+        #[test]
+        fn select_top_k_agrees_with_a_full_sort(
+            // few distinct values, so there are ties
+            scores in prop::collection::vec((0..20u8).prop_map(f32::from), 0..200),
+            k in 0usize..220,
+        ) {
+            // both break ties by the lower index, so not only the sets but the orders agree
+            prop_assert_eq!(select_top_k(&scores, k), top_k_by_sorting(&scores, k));
+        }
+    }
+
+    #[test]
+    fn roulette_picks_proportionally_and_uniformly_without_scores() {
+        let mut rng = StdRng::seed_from_u64(8);
+        let draws = |scores: &[f32], rng: &mut StdRng| {
+            let mut counts = vec![0; scores.len()];
+            for _ in 0..4000 {
+                counts[select_roulette(scores, rng)] += 1;
+            }
+            counts
+        };
+
+        assert_eq!(draws(&[0.0, 0.0, 10.0, 0.0], &mut rng), [0, 0, 4000, 0]);
+        assert_eq!(draws(&[1.0, 0.0, 3.0, -5.0], &mut rng)[1], 0);
+        // no positive scores, every index is as likely
+        for zeros in [[0.0; 4], [-1.0; 4]] {
+            for count in draws(&zeros, &mut rng) {
+                assert!((900..1100).contains(&count), "{} of 4000 draws", count);
+            }
+        }
+    }
+
+    #[test]
+    fn insertions_and_deletions_change_the_length_by_one() {
+        let mut rng = StdRng::seed_from_u64(5);
+        let genom = random_genom(6, 4);
+
+        let mut grown = Genom::from_previous(&genom);
+        grown.mutate_insert(&mut rng);
+        assert_eq!(grown.nucleotides.len(), 7);
+        // the old nucleotides keep their order around the new one
+        let new = (0..6).find(|&i| grown.nucleotides[i] != genom.nucleotides[i]).unwrap_or(6);
+        let mut without = grown.nucleotides.clone();
+        without.remove(new);
+        assert_eq!(without, genom.nucleotides);
+
+        let mut shrunk = Genom::from_previous(&genom);
+        for len in (1..6).rev() {
+            shrunk.mutate_delete(&mut rng);
+            assert_eq!(shrunk.nucleotides.len(), len);
+        }
+        shrunk.mutate_delete(&mut rng);
+        assert_eq!(shrunk.nucleotides.len(), 1);
+        assert!(genom.nucleotides.contains(&shrunk.nucleotides[0]));
+    }
+
+    #[test]
+    fn cut_truncates_and_shift_rotates() {
+        let genom = || Genom::<AndiN, AndiS>::from_iter((1..=5).map(AndiN::decode));
+        let decoded = |encoded: &[u32]| -> Vec<AndiN> {
+            encoded.iter().copied().map(AndiN::decode).collect()
+        };
+
+        let mut cut = genom();
+        cut.cut(3);
+        assert_eq!(cut.nucleotides, decoded(&[1, 2, 3]));
+        cut.cut(10);
+        assert_eq!(cut.nucleotides, decoded(&[1, 2, 3]));
+        cut.cut(0);
+        assert!(cut.nucleotides.is_empty());
+
+        let mut shifted = genom();
+        shifted.shift();
+        assert_eq!(shifted.nucleotides, decoded(&[2, 3, 4, 5, 1]));
+    }
+
+    #[test]
+    fn crossing_at_3_takes_the_first_3_nucleotides_from_a() {
+        let (a, b) = (random_genom(8, 5), random_genom(8, 6));
+        let expected = [true, true, true, false, false, false, false, false];
+
+        assert_eq!(from_a(&Genom::crossover_at(&a, &b, 3), &a), expected);
+        assert_eq!(from_a(&Genom::crossover_cut_at(&a, &b, 3), &a), expected);
+        let mut child = Genom::with_capacity(8);
+        Genom::crossover_mut_at(&a, &b, &mut child, 3);
+        assert_eq!(from_a(&child, &a), expected);
+        // the rest comes from `b`
+        assert_eq!(child.nucleotides[3..], b.nucleotides[3..]);
+    }
+
+    #[test]
+    fn every_crossover_handles_empty_and_single_nucleotide_genoms() {
+        type Crossover = fn(&AndiGenom, &AndiGenom, &mut StdRng) -> AndiGenom;
+        let crossovers: [(&str, Crossover); 10] = [
+            ("crossover", |a, b, rng| Genom::crossover(a, b, rng)),
+            ("crossover_at", |a, b, _| Genom::crossover_at(a, b, 1)),
+            ("crossover_cut", |a, b, rng| Genom::crossover_cut(a, b, rng)),
+            ("crossover_cut_at", |a, b, _| Genom::crossover_cut_at(a, b, 1)),
+            ("crossover_two_point", |a, b, rng| Genom::crossover_two_point(a, b, rng)),
+            ("crossover_two_point_at", |a, b, _| Genom::crossover_two_point_at(a, b, 0, 1)),
+            ("crossover_uniform", |a, b, rng| Genom::crossover_uniform(a, b, 0.5, rng)),
+            ("crossover_4th", |a, b, _| Genom::crossover_4th(a, b)),
+            ("crossover_mut", |a, b, rng| {
+                let mut child = Genom::with_capacity(1);
+                Genom::crossover_mut(a, b, &mut child, rng);
+                child
+            }),
+            ("crossover_mut_at", |a, b, _| {
+                let mut child = Genom::with_capacity(1);
+                Genom::crossover_mut_at(a, b, &mut child, 1);
+                child
+            }),
+        ];
+        let (empty, single, other) = (random_genom(0, 1), random_genom(1, 2), random_genom(1, 3));
+        let mut rng = StdRng::seed_from_u64(7);
+
+        for (name, crossover) in crossovers {
+            assert!(crossover(&empty, &empty, &mut rng).nucleotides.is_empty(), "{}", name);
+            for (a, b) in [(&single, &other), (&single, &empty), (&empty, &single)] {
+                let child = crossover(a, b, &mut rng);
+                assert_eq!(child.nucleotides.len(), 1, "{}", name);
+                let parents = [a.nucleotides.first(), b.nucleotides.first()];
+                assert!(parents.contains(&child.nucleotides.first()), "{}", name);
+            }
+        }
+    }
+}
@@ -0,0 +1,134 @@
+use std::{collections::VecDeque, io};
+
+use crate::creature::GenerationStats;
+
+// -------------------------------------------------------------------------------------------------
+// --- History -------------------------------------------------------------------------------------
+// -------------------------------------------------------------------------------------------------
+
+/// Per-generation statistics of a simulation run.
+///
+/// Only the last `capacity` generations are kept, so long runs don't grow it unbounded.
+pub struct History {
+    capacity: usize,
+    records: VecDeque<(u32, GenerationStats)>,
+}
+
+impl History {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            records: VecDeque::with_capacity(capacity),
+        }
+    }
+
+    /// records the stats of a finished generation, dropping the oldest record if full
+    pub fn push(&mut self, generation: u32, stats: GenerationStats) {
+        if self.capacity == 0 {
+            return;
+        }
+        while self.records.len() >= self.capacity {
+            self.records.pop_front();
+        }
+        self.records.push_back((generation, stats));
+    }
+
+    /// iterates over `(generation, survivors, best_score, mean_score, diversity)`, oldest first
+    pub fn iter(&self) -> impl Iterator<Item = (u32, usize, f32, f32, f32)> + '_ {
+        self.records.iter().map(|(gen, stats)| {
+            (*gen, stats.survivors, stats.best_score, stats.mean_score, stats.diversity)
+        })
+    }
+
+    /// `(generation, stat)` of every record, oldest first, e.g. to plot one of the stats
+    pub fn series(&self, stat: impl Fn(&GenerationStats) -> f32) -> Vec<(f64, f64)> {
+        self.records
+            .iter()
+            .map(|(gen, stats)| (*gen as f64, stat(stats) as f64))
+            .collect()
+    }
+
+    /// the most recent record
+    pub fn last(&self) -> Option<&(u32, GenerationStats)> {
+        self.records.back()
+    }
+
+    pub fn len(&self) -> usize {
+        self.records.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// changes the capacity, dropping the oldest records if there are too many
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity;
+        while self.records.len() > capacity {
+            self.records.pop_front();
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.records.clear();
+    }
+
+    /// writes all records as CSV, including the header
+    pub fn write_csv<W: io::Write>(&self, mut writer: W) -> io::Result<()> {
+        writeln!(writer, "{}", CSV_HEADER)?;
+        for (generation, stats) in self.records.iter() {
+            write_csv_row(&mut writer, *generation, stats)?;
+        }
+        Ok(())
+    }
+}
+
+/// column names of the CSV written by [`History::write_csv`]
+pub const CSV_HEADER: &str = "generation,survivors,best_score,mean_score,diversity";
+
+/// writes the stats of a generation as a CSV row. Rust's float formatting doesn't depend on the
+/// locale, the decimal separator is always `.`.
+pub fn write_csv_row<W: io::Write>(
+    writer: &mut W,
+    generation: u32,
+    stats: &GenerationStats,
+) -> io::Result<()> {
+    writeln!(
+        writer,
+        "{},{},{},{},{}",
+        generation, stats.survivors, stats.best_score, stats.mean_score, stats.diversity
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stats(survivors: usize, mean_score: f32) -> GenerationStats {
+        GenerationStats {
+            survivors,
+            mean_score,
+            ..GenerationStats::default()
+        }
+    }
+
+    #[test]
+    fn series_gives_the_plot_data_of_the_kept_generations() {
+        let mut history = History::new(3);
+        assert!(history.series(|s| s.mean_score).is_empty());
+        for generation in 0..5 {
+            history.push(generation, stats(10 * generation as usize, generation as f32 / 4.0));
+        }
+
+        assert_eq!(history.len(), 3);
+        assert_eq!(
+            history.series(|s| s.survivors as f32),
+            [(2.0, 20.0), (3.0, 30.0), (4.0, 40.0)]
+        );
+        assert_eq!(history.series(|s| s.mean_score), [(2.0, 0.5), (3.0, 0.75), (4.0, 1.0)]);
+    }
+}
@@ -0,0 +1,31 @@
+//! The simulation: creatures with genom-encoded brains moving on a board, selected and bred
+//! generation after generation. `main.rs` puts it into a window (or runs it headless).
+
+pub mod andis;
+pub mod board;
+pub mod brain;
+pub mod config;
+pub mod creature;
+pub mod experiment;
+pub mod genes;
+pub mod history;
+pub mod objective;
+pub mod population;
+pub mod recorder;
+pub mod spatial;
+pub mod traits;
+pub mod ui;
+pub mod utils;
+pub mod world;
+pub mod zone;
+
+// dimensions
+pub const BWIDTH: u32 = 512;
+pub const BHEIGHT: u32 = 512;
+
+// simulation
+pub const N_CREATURES: usize = 1000;
+pub const GENOME_LENGTH: usize = 5;
+pub const N_INTERNAL_NEURONS: usize = 0;
+pub const MUT_COEFF: usize = 1000;
+pub const STEPS_IN_GENERATION: u32 = 500;
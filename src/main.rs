@@ -1,83 +1,131 @@
-mod andis;
-mod creature;
-mod genes;
-mod ui;
-mod utils;
-mod world;
-
-use std::{ops::Add, time::Instant};
-
-use egui::{vec2, Color32, Image, Layout};
-use egui_sdl2_gl::DpiScaling;
-use sdl2::{event::Event, pixels, rect::Rect, video::SwapInterval};
-
-use crate::{
-    andis::AndiN,
-    ui::{setup_gui, Egui_Ctx},
+#[cfg(feature = "desktop")]
+mod sdl;
+
+#[cfg(feature = "desktop")]
+use std::time::Instant;
+
+#[cfg(feature = "desktop")]
+use egui::{vec2, Image, Key, Layout, Sense};
+#[cfg(feature = "desktop")]
+use rand::SeedableRng;
+use rand::Rng;
+
+#[cfg(feature = "desktop")]
+use brains::ui::{draw_frame_times, Backend};
+use brains::{
+    andis::{population_checksum, AndiN},
+    config::SimConfig,
+    experiment::{run_sweep, SweepParam},
     world::World,
 };
 
+/// the window the GUI runs in, picked by the enabled features. Without one only `--headless` and
+/// `--sweep` runs are possible.
+#[cfg(feature = "desktop")]
+type Gui = sdl::SdlBackend;
+
 // dimensions
+#[cfg(feature = "desktop")]
 const WWIDTH: u32 = 1600;
+#[cfg(feature = "desktop")]
 const WHEIGHT: u32 = 1024;
-const BWIDTH: u32 = 512;
-const BHEIGHT: u32 = 512;
+#[cfg(feature = "desktop")]
 const ZOOM: f32 = 2.0;
 
 // fps
+#[cfg(feature = "desktop")]
 const FRAMETIME: u32 = 0;
 const SIMS_PER_FRAME: u32 = 10;
 
-// simulation
-const N_CREATURES: usize = 1000;
-const N_NEURONS: usize = 5;
-const MUT_COEFF: usize = 1;
-const STEPS_IN_GENERATION: u32 = 500;
-
 // -------------------------------------------------------------------------------------------------
 
 fn main() {
-    println!("Hello, world!");
+    // info and up by default, `RUST_LOG=debug` also dumps a brain every generation
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
+
+    let mut config = load_config();
+    if let Some(seed) = arg_value("--seed") {
+        config.seed = Some(seed.parse().expect("--seed expects an unsigned integer"));
+    }
+
+    // sweeps without a seed use a fixed one, see `run_sweep`
+    if let Some(values) = arg_value("--sweep") {
+        let generations = arg_value("--headless")
+            .and_then(|generations| generations.parse().ok())
+            .expect("--sweep needs the number of generations, e.g. `--headless 100`");
+        sweep(&config, &values, generations);
+        return;
+    }
+
+    let seed = config.seed.unwrap_or_else(rand::random);
+    config.seed = Some(seed);
+    log::info!("seed: {}", seed);
 
-    let mut rng = rand::thread_rng();
-    let mut world: World<_, AndiN> = World::new(
-        N_CREATURES,
-        N_NEURONS,
-        MUT_COEFF,
-        BWIDTH as i32,
-        BHEIGHT as i32,
-        rng,
-    );
-    world.set_steps_in_generation(STEPS_IN_GENERATION);
+    let mut world: World<_, AndiN> = config.builder().build();
+    world.set_sims_per_frame(SIMS_PER_FRAME);
 
-    let mut egui_ctx = setup_gui(WWIDTH, WHEIGHT, BWIDTH, BHEIGHT, FRAMETIME);
+    if let Some(generations) = arg_value("--headless") {
+        let generations = generations
+            .parse()
+            .expect("--headless expects the number of generations");
+        run_headless(&mut world, generations);
+        return;
+    }
+
+    run_gui(&mut world);
+}
+
+/// opens the window and runs the simulation until it is closed
+#[cfg(feature = "desktop")]
+fn run_gui<R: Rng + SeedableRng>(world: &mut World<R, AndiN>) {
+    let (board_width, board_height) = world.board_size();
+    let (board_width, board_height) = (board_width as u32, board_height as u32);
+    let mut gui = Gui::new(WWIDTH, WHEIGHT, board_width, board_height, FRAMETIME);
 
     let mut framecount = 0;
     let mut last_frametime = Instant::now();
     let mut fps = 0.0;
-    let mut next_preview_frame = 0;
+    let mut last_generation = world.generation();
 
     'running: loop {
-        egui_ctx.begin_frame();
-        world.draw(&mut egui_ctx.srgba);
-        egui_ctx.update_texture();
+        gui.begin_frame();
+        if !world.max_speed() {
+            if world.show_heatmap() {
+                world.draw_heatmap(gui.pixels());
+            } else {
+                world.draw(gui.pixels());
+            }
+            gui.update_texture();
+        }
 
-        egui::TopBottomPanel::top("hello").show(&egui_ctx.egui_ctx, |ui| {
-            ui.label(format!("FPS: {:.2}", fps));
+        egui::TopBottomPanel::top("hello").show(gui.ctx(), |ui| {
+            ui.horizontal(|ui| {
+                ui.label(format!("FPS: {:.2}", fps));
+                draw_frame_times(ui, gui.frame_times());
+            });
         });
 
-        egui::SidePanel::right("details").show(&egui_ctx.egui_ctx, |ui| {
+        egui::SidePanel::right("details").show(gui.ctx(), |ui| {
             world.details_ui(ui);
         });
 
-        egui::CentralPanel::default().show(&egui_ctx.egui_ctx, |ui| {
+        egui::CentralPanel::default().show(gui.ctx(), |ui| {
             ui.with_layout(
                 Layout::centered_and_justified(egui::Direction::LeftToRight),
                 |ui| {
-                    ui.add(Image::new(
-                        egui_ctx.tex_id,
-                        vec2(BWIDTH as f32 * ZOOM, BHEIGHT as f32 * ZOOM),
-                    ));
+                    let response = ui.add(
+                        Image::new(
+                            gui.texture_id(),
+                            vec2(board_width as f32 * ZOOM, board_height as f32 * ZOOM),
+                        )
+                        .sense(Sense::click()),
+                    );
+                    if response.clicked() {
+                        if let Some(pos) = response.interact_pointer_pos() {
+                            let cell = (pos - response.rect.min) / ZOOM;
+                            world.select_creature_at(cell.x as i32, cell.y as i32);
+                        }
+                    }
                     framecount += 1;
                 },
             );
@@ -90,18 +138,121 @@ fn main() {
             framecount = 0;
         }
 
-        if next_preview_frame == world.generation() {
-            world.simulate();
-        } else if next_preview_frame < world.generation() {
-            next_preview_frame += SIMS_PER_FRAME;
-            println!("end of generation. one random brain:");
-            println!("{}", world.creatures.genoms[0]);
-        } else {
+        if world.paused() {
+            if world.take_single_step() {
+                world.simulate();
+            }
+        } else if world.max_speed() {
             world.simulate_until_endofgeneration();
+        } else {
+            for _ in 0..world.sims_per_frame() {
+                world.simulate();
+            }
         }
 
-        if !egui_ctx.end_frame() {
+        if last_generation < world.generation() {
+            last_generation = world.generation();
+            log::info!("end of generation {}", last_generation);
+            if let Some(genom) = world.creatures.genoms().first() {
+                log::debug!("one random brain:\n{}", genom);
+            }
+        }
+
+        if !gui.end_frame() {
             break 'running;
         }
+
+        if gui.key_pressed(Key::Space) {
+            world.toggle_paused();
+        }
+        if gui.key_pressed(Key::D) {
+            if let Some(genom) = world.selected_genom() {
+                println!("selected brain:");
+                println!("{}", genom);
+                for synapse in genom.iter_connections(world.creatures.n_internal_neurons) {
+                    println!("  {:?} -> {:?}: {:.2}", synapse.source, synapse.sink, synapse.weight);
+                }
+            }
+        }
+        if world.take_restart() {
+            let seed = rand::random();
+            log::info!("restart, seed: {}", seed);
+            world.reset(seed);
+            last_generation = world.generation();
+        }
+    }
+}
+
+/// without a GUI backend there is nothing to show, see `--headless`
+#[cfg(not(feature = "desktop"))]
+fn run_gui<R: Rng>(_world: &mut World<R, AndiN>) {
+    log::error!("built without the `desktop` feature, run with `--headless <generations>`");
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// returns the value following the command line flag `name`, e.g. `--headless 100`
+fn arg_value(name: &str) -> Option<String> {
+    let mut args = std::env::args().skip_while(|arg| arg != name);
+    args.next()?;
+    Some(args.next().unwrap_or_default())
+}
+
+/// the config given by `--config path`, or the defaults
+#[cfg(feature = "serde")]
+fn load_config() -> SimConfig {
+    match arg_value("--config") {
+        Some(path) => SimConfig::load(&path)
+            .unwrap_or_else(|err| panic!("could not load the config {}: {}", path, err)),
+        None => SimConfig::default(),
+    }
+}
+
+/// loading a config file needs the `serde` feature, without it the defaults are used
+#[cfg(not(feature = "serde"))]
+fn load_config() -> SimConfig {
+    if arg_value("--config").is_some() {
+        log::warn!("--config needs the `serde` feature, using the defaults");
     }
+    SimConfig::default()
+}
+
+/// runs a headless sweep given as `name=value,...` (see [`SweepParam::parse_sweep`]), printing
+/// the final stats of each run
+fn sweep(config: &SimConfig, spec: &str, generations: u32) {
+    let params = SweepParam::parse_sweep(spec)
+        .unwrap_or_else(|err| panic!("--sweep expects `name=value,...`: {}", err));
+    for result in run_sweep(&params, generations, config) {
+        let stats = &result.final_stats;
+        println!(
+            "{:?} (seed {}) after {} generations: survivors {}, best score {:.3}, mean score {:.3}",
+            result.param,
+            result.config.seed.unwrap_or_default(),
+            result.generations,
+            stats.survivors,
+            stats.best_score,
+            stats.mean_score
+        );
+    }
+}
+
+/// runs the simulation without GUI, printing the stats of each generation
+fn run_headless<R: Rng>(world: &mut World<R, AndiN>, generations: u32) {
+    world.advance_generations(generations, |report| {
+        let stats = &report.stats;
+        println!(
+            "gen {} ({}/{}): survivors {}, best score {:.3}, mean score {:.3}, diversity {:.2}",
+            report.generation,
+            report.completed,
+            generations,
+            stats.survivors,
+            stats.best_score,
+            stats.mean_score,
+            stats.diversity
+        );
+        true
+    });
+    println!("one random brain:");
+    println!("{}", world.creatures.genoms()[0]);
+    println!("population checksum: {:016x}", population_checksum(world.creatures.genoms()));
 }
@@ -0,0 +1,141 @@
+use crate::{
+    board::Board,
+    creature::{Creatures, NeuronNucl},
+};
+
+/// radius of the square in which [`MinimizeCrowding`] counts neighbours
+const CROWDING_RADIUS: i32 = 2;
+
+// -------------------------------------------------------------------------------------------------
+// --- Objective -----------------------------------------------------------------------------------
+// -------------------------------------------------------------------------------------------------
+
+/// What the creatures are selected for: scores a creature at the end of a generation, the higher
+/// the better.
+///
+/// It replaces [`NeuronNucl::score`] (the distance to the selection zone) when set on the world,
+/// see [`objectives`] for the ones to pick from.
+pub trait Objective<N: NeuronNucl> {
+    /// the name shown in the UI
+    fn name(&self) -> &str;
+
+    /// the score of creature `creature_idx` on the `board`, whose occupancy grid is up to date
+    fn evaluate(&self, creature_idx: usize, creatures: &Creatures<N>, board: &Board) -> f32;
+}
+
+/// all objectives, in the order they are offered in the UI
+pub fn objectives<N: NeuronNucl>() -> Vec<Box<dyn Objective<N>>> {
+    vec![
+        Box::new(ReachRight),
+        Box::new(ReachCenter),
+        Box::new(MaximizeFood),
+        Box::new(MinimizeCrowding),
+    ]
+}
+
+/// the further right the better, `1.0` at the right edge
+pub struct ReachRight;
+
+impl<N: NeuronNucl> Objective<N> for ReachRight {
+    fn name(&self) -> &str {
+        "reach right"
+    }
+
+    fn evaluate(&self, creature_idx: usize, creatures: &Creatures<N>, board: &Board) -> f32 {
+        creatures.positions[creature_idx].x as f32 / (board.width - 1).max(1) as f32
+    }
+}
+
+/// the closer to the center the better, `1.0` in the center and `0.0` in the corners
+pub struct ReachCenter;
+
+impl<N: NeuronNucl> Objective<N> for ReachCenter {
+    fn name(&self) -> &str {
+        "reach center"
+    }
+
+    fn evaluate(&self, creature_idx: usize, creatures: &Creatures<N>, board: &Board) -> f32 {
+        let pos = &creatures.positions[creature_idx];
+        let (cx, cy) = ((board.width - 1) as f32 / 2.0, (board.height - 1) as f32 / 2.0);
+        let distance = (pos.x as f32 - cx).hypot(pos.y as f32 - cy);
+        1.0 - distance / cx.hypot(cy).max(1.0)
+    }
+}
+
+/// the more food eaten during the generation the better
+pub struct MaximizeFood;
+
+impl<N: NeuronNucl> Objective<N> for MaximizeFood {
+    fn name(&self) -> &str {
+        "maximize food"
+    }
+
+    fn evaluate(&self, creature_idx: usize, creatures: &Creatures<N>, _board: &Board) -> f32 {
+        creatures.food_eaten[creature_idx] as f32
+    }
+}
+
+/// the fewer other creatures nearby the better, `1.0` if there are none
+pub struct MinimizeCrowding;
+
+impl<N: NeuronNucl> Objective<N> for MinimizeCrowding {
+    fn name(&self) -> &str {
+        "minimize crowding"
+    }
+
+    /// counts the neighbours on the occupancy grid of the `board`, wrapping around the edges of a
+    /// toroidal board
+    fn evaluate(&self, creature_idx: usize, creatures: &Creatures<N>, board: &Board) -> f32 {
+        let neighbors = board.count_neighbors(&creatures.positions[creature_idx], CROWDING_RADIUS);
+        1.0 / (1.0 + neighbors as f32)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        andis::AndiN,
+        board::BoundaryMode,
+        creature::Point2,
+        genes::Genom,
+    };
+
+    /// creatures with empty brains at `positions` on a 16 x 16 board
+    fn creatures_at(positions: &[(i32, i32)], boundary: BoundaryMode) -> (Creatures<AndiN>, Board) {
+        let genoms = positions.iter().map(|_| Genom::from_iter(Vec::<AndiN>::new())).collect();
+        let positions = positions.iter().map(|&(x, y)| Point2::new(x, y)).collect();
+        let creatures = Creatures::from_genoms(genoms, positions);
+        let mut board = Board::new(16, 16);
+        board.boundary = boundary;
+        board.fill(&creatures.positions);
+        (creatures, board)
+    }
+
+    fn scores(
+        objective: &dyn Objective<AndiN>,
+        creatures: &Creatures<AndiN>,
+        board: &Board,
+    ) -> Vec<f32> {
+        (0..creatures.positions.len()).map(|i| objective.evaluate(i, creatures, board)).collect()
+    }
+
+    #[test]
+    fn reach_right_scores_the_rightmost_creatures_highest() {
+        let positions = [(3, 0), (15, 4), (0, 9), (8, 15)];
+        let (creatures, board) = creatures_at(&positions, BoundaryMode::Clamp);
+        let scores = scores(&ReachRight, &creatures, &board);
+
+        assert_eq!(scores, [0.2, 1.0, 0.0, 8.0 / 15.0]);
+    }
+
+    #[test]
+    fn minimize_crowding_counts_the_neighbours_around_the_edges_of_a_toroidal_board() {
+        let positions = [(0, 8), (15, 8), (8, 8)];
+        let (creatures, board) = creatures_at(&positions, BoundaryMode::Clamp);
+        assert_eq!(scores(&MinimizeCrowding, &creatures, &board), [1.0, 1.0, 1.0]);
+
+        let (creatures, board) = creatures_at(&positions, BoundaryMode::Wrap);
+        assert_eq!(scores(&MinimizeCrowding, &creatures, &board), [0.5, 0.5, 1.0]);
+    }
+}
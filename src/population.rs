@@ -0,0 +1,487 @@
+use rand::Rng;
+
+use crate::{
+    genes::{
+        crossover_parallel, mating_indices, select_top_k, Genom, Nucl, Scorer, SelectionStrategy,
+    },
+    traits::Traits,
+};
+
+/// default probability of a child to gain a nucleotide, and of it to lose one
+const DEFAULT_INDEL_RATE: f64 = 0.01;
+/// default probability of each of a child's [`Traits`] to mutate
+const DEFAULT_TRAIT_MUTATION_RATE: f64 = 0.1;
+
+/// what to do when no genom (of a species) qualifies as a parent at the end of a generation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ExtinctionPolicy {
+    /// stops the simulation
+    Panic,
+    /// the species starts over with random genoms
+    Reseed,
+    /// the best scoring genoms become parents anyway, or reseeds if none is left at all
+    #[default]
+    KeepBest,
+}
+
+// -------------------------------------------------------------------------------------------------
+// --- Population ----------------------------------------------------------------------------------
+// -------------------------------------------------------------------------------------------------
+
+/// A population of genoms together with the parameters of how it evolves: which genoms become
+/// parents, how they mate and how their children mutate.
+///
+/// It doesn't know anything about the world the genoms live in, scoring them is up to the owner.
+///
+/// The genoms may be split into several species that never interbreed, each keeps an equal share
+/// of `size`. A species is a tag in `species` rather than a population of its own: all creatures
+/// stay in one index space, so the board's occupancy, the neighbor senses and predation work
+/// across species without translating between collections, and removing creatures keeps the tags
+/// aligned like any other per-creature vector.
+///
+/// Every genom comes with its [`Traits`], which are inherited from the same two parents.
+pub struct Population<N: Nucl, S: Scorer> {
+    pub genoms: Vec<Genom<N, S>>,
+    /// the species of each genom, index-aligned with `genoms`
+    pub species: Vec<usize>,
+    /// the traits of each genom, index-aligned with `genoms`
+    pub traits: Vec<Traits>,
+    /// the number of species, see [`Population::set_species`]
+    pub n_species: usize,
+    /// the number of genoms each new generation starts with
+    pub size: usize,
+    /// the number of nucleotides of a random genom
+    pub genome_length: usize,
+    pub mutation_coeff: usize,
+    /// if set, overrides the mutation probability of `1/mutation_coeff`
+    pub mutation_rate: Option<f64>,
+    /// probability of a child to gain a random nucleotide, and independently of it to lose one
+    pub indel_rate: f64,
+    /// probability of each of a child's traits to mutate, independent of the genom's mutations
+    pub trait_mutation_rate: f64,
+    /// the fraction of the population (the best scoring ones) allowed to reproduce
+    pub parent_ratio: f32,
+    /// how the parents pick their mating partners
+    pub selection: SelectionStrategy,
+    /// number of best genoms copied unchanged into the next generation
+    pub n_elite: usize,
+    /// if set, the elites keep their scores so a simulator doesn't score them again
+    pub keep_elite_scores: bool,
+    /// number of threads breeding the next generation, see [`crossover_parallel`]
+    pub threads: usize,
+    /// what happens if nobody survives, see [`Population::next_generation`]
+    pub extinction: ExtinctionPolicy,
+}
+
+impl<N: Nucl, S: Scorer> Population<N, S> {
+    /// a population of the given genoms, its size stays the number of genoms
+    pub fn new(genoms: Vec<Genom<N, S>>, mutation_coeff: usize) -> Self {
+        Self {
+            size: genoms.len(),
+            genome_length: genoms.first().map_or(0, |g| g.nucleotides.len()),
+            species: vec![0; genoms.len()],
+            traits: vec![Traits::default(); genoms.len()],
+            n_species: 1,
+            genoms,
+            mutation_coeff,
+            mutation_rate: None,
+            indel_rate: DEFAULT_INDEL_RATE,
+            trait_mutation_rate: DEFAULT_TRAIT_MUTATION_RATE,
+            parent_ratio: 0.5,
+            selection: SelectionStrategy::default(),
+            n_elite: 0,
+            keep_elite_scores: false,
+            threads: 1,
+            extinction: ExtinctionPolicy::default(),
+        }
+    }
+
+    /// `n` random genoms of `genome_length` nucleotides
+    pub fn random<R: Rng>(
+        n: usize,
+        genome_length: usize,
+        mutation_coeff: usize,
+        rng: &mut R,
+    ) -> Self {
+        let mut population = Self::new(
+            (0..n).map(|_| Genom::random(genome_length, rng)).collect(),
+            mutation_coeff,
+        );
+        population.randomize_traits(rng);
+        population
+    }
+
+    /// replaces the genoms by `size` random ones, keeping the evolution parameters
+    pub fn randomize<R: Rng>(&mut self, rng: &mut R) {
+        self.genoms.clear();
+        let genome_length = self.genome_length;
+        self.genoms.extend((0..self.size).map(|_| Genom::random(genome_length, rng)));
+        self.species = self.species_blocks();
+        self.randomize_traits(rng);
+        self.mutation_rate = None;
+    }
+
+    /// gives every genom random traits
+    pub fn randomize_traits<R: Rng>(&mut self, rng: &mut R) {
+        self.traits = (0..self.genoms.len()).map(|_| Traits::random(rng)).collect();
+    }
+
+    /// splits the genoms into `n_species` species of (about) equal size, in order of the genoms
+    pub fn set_species(&mut self, n_species: usize) {
+        self.n_species = n_species.max(1);
+        self.species = self.species_blocks();
+    }
+
+    /// the species of consecutive blocks of the genoms, the first block is species `0`
+    fn species_blocks(&self) -> Vec<usize> {
+        let n = self.genoms.len();
+        (0..n).map(|i| i * self.n_species / n.max(1)).collect()
+    }
+
+    /// the number of genoms of `species` in each new generation
+    fn species_size(&self, species: usize) -> usize {
+        let n_species = self.n_species.max(1);
+        self.size * (species + 1) / n_species - self.size * species / n_species
+    }
+
+    pub fn len(&self) -> usize {
+        self.genoms.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.genoms.is_empty()
+    }
+
+    pub fn best_score(&self) -> f32 {
+        self.genoms.iter().map(|g| g.score()).fold(0.0, f32::max)
+    }
+
+    pub fn mean_score(&self) -> f32 {
+        self.genoms.iter().map(|g| g.score()).sum::<f32>() / self.genoms.len().max(1) as f32
+    }
+
+    /// the probability of each nucleotide of a child to mutate
+    pub fn mutation_probability(&self) -> f64 {
+        self.mutation_rate.unwrap_or(1.0 / self.mutation_coeff.max(1) as f64)
+    }
+
+    /// Picks the parents of the next generation: the best scoring `parent_ratio` (of a species'
+    /// share of the population) of the `candidates` (indices into `genoms`), best first. All
+    /// genoms are candidates if `candidates` is `None`.
+    pub fn select_parents(&self, candidates: Option<&[usize]>) -> Vec<usize> {
+        let candidates: Vec<_> = match candidates {
+            Some(candidates) => candidates.to_vec(),
+            None => (0..self.genoms.len()).collect(),
+        };
+        let size = self.size / self.n_species.max(1);
+        let n_parents = ((size as f32 * self.parent_ratio).ceil() as usize)
+            .max(1)
+            .min(candidates.len());
+
+        let scores: Vec<_> = candidates.iter().map(|&i| self.genoms[i].score()).collect();
+        select_top_k(&scores, n_parents).into_iter().map(|i| candidates[i]).collect()
+    }
+
+    /// Replaces the genoms by the next generation of `size` genoms, each species bred from its own
+    /// parents chosen by [`Population::select_parents`]. The elites of all species go first and
+    /// unchanged. Returns the number of elites.
+    ///
+    /// If a species has no candidates (or no genoms) left it is extinct and `extinction` decides
+    /// how it goes on.
+    pub fn next_generation<R: Rng>(&mut self, candidates: Option<&[usize]>, rng: &mut R) -> usize
+    where
+        N: Send + Sync,
+        S: Clone + Send + Sync,
+    {
+        let n_species = self.n_species.max(1);
+
+        // the parents of each species, `None` if it has to start over
+        let mut parents = Vec::with_capacity(n_species);
+        for species in 0..n_species {
+            let of_species = |i: &usize| self.species[*i] == species;
+            let alive: Vec<_> = (0..self.genoms.len()).filter(of_species).collect();
+            let mut members = match candidates {
+                Some(candidates) => candidates.iter().copied().filter(of_species).collect(),
+                None => alive.clone(),
+            };
+            if members.is_empty() {
+                match self.extinction {
+                    ExtinctionPolicy::Panic => panic!("species {} died out", species),
+                    ExtinctionPolicy::KeepBest if !alive.is_empty() => {
+                        log::warn!("species {} died out, keeping the best", species);
+                        members = alive;
+                    }
+                    _ => {
+                        log::warn!("species {} died out, reseeding", species);
+                        parents.push(None);
+                        continue;
+                    }
+                }
+            }
+            parents.push(Some(self.select_parents(Some(&members))));
+        }
+        let n_parents: usize = parents.iter().flatten().map(Vec::len).sum();
+        log::info!("surviving parents: {}", n_parents);
+
+        let copy = if self.keep_elite_scores {
+            Genom::from_previous_with_score
+        } else {
+            Genom::from_previous
+        };
+        let mut next = Vec::with_capacity(self.size);
+        let mut next_species = Vec::with_capacity(self.size);
+        let mut next_traits = Vec::with_capacity(self.size);
+        let mut elites = Vec::with_capacity(n_species);
+        for (species, parents) in parents.iter().enumerate() {
+            let parents = parents.as_deref().unwrap_or(&[]);
+            let n_elite = self.n_elite.min(parents.len()).min(self.species_size(species));
+            next.extend(parents[..n_elite].iter().map(|&i| copy(&self.genoms[i])));
+            next_traits.extend(parents[..n_elite].iter().map(|&i| self.traits[i]));
+            next_species.extend(std::iter::repeat_n(species, n_elite));
+            elites.push(n_elite);
+        }
+        let n_elite = next.len();
+
+        for (species, parents) in parents.iter().enumerate() {
+            let n_children = self.species_size(species) - elites[species];
+            match parents {
+                Some(parents) => {
+                    let genoms: Vec<_> = parents.iter().map(|&i| &self.genoms[i]).collect();
+                    let pairs = mating_indices(&genoms, n_children, self.selection, rng);
+                    let mates: Vec<_> =
+                        pairs.iter().map(|&(a, b)| (genoms[a], genoms[b])).collect();
+                    next.extend(crossover_parallel(&mates, self.threads, rng));
+                    next_traits.extend(pairs.iter().map(|&(a, b)| {
+                        let (a, b) = (&self.traits[parents[a]], &self.traits[parents[b]]);
+                        Traits::crossover(a, b, rng)
+                    }));
+                }
+                None => {
+                    let genome_length = self.genome_length;
+                    next.extend((0..n_children).map(|_| Genom::random(genome_length, rng)));
+                    next_traits.extend((0..n_children).map(|_| Traits::random(rng)));
+                }
+            }
+            next_species.extend(std::iter::repeat_n(species, n_children));
+        }
+        self.genoms = next;
+        self.species = next_species;
+        self.traits = next_traits;
+
+        n_elite
+    }
+
+    /// mutates every genom but the first `n_skip` (the elites), including the rare insertions and
+    /// deletions of nucleotides, and their traits
+    pub fn mutate<R: Rng>(&mut self, n_skip: usize, rng: &mut R) {
+        let prob = self.mutation_probability();
+        let indel_rate = self.indel_rate.clamp(0.0, 1.0);
+        for genom in self.genoms.iter_mut().skip(n_skip) {
+            genom.mutate_all(prob, rng);
+            if rng.gen_bool(indel_rate) {
+                genom.mutate_insert(rng);
+            }
+            if rng.gen_bool(indel_rate) {
+                genom.mutate_delete(rng);
+            }
+        }
+        for traits in self.traits.iter_mut().skip(n_skip) {
+            traits.mutate(self.trait_mutation_rate, rng);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::andis::{population_checksum, AndiN, AndiS};
+    use rand::{rngs::StdRng, SeedableRng};
+
+    /// 64 random genoms of 16 nucleotides, each scored by its index
+    fn scored_population(seed: u64) -> Population<AndiN, AndiS> {
+        let mut population = Population::random(64, 16, 100, &mut StdRng::seed_from_u64(seed));
+        for (i, genom) in population.genoms.iter_mut().enumerate() {
+            genom.set_score(AndiS::new(i as f32));
+        }
+        population
+    }
+
+    #[test]
+    fn next_generation_does_not_depend_on_the_number_of_threads() {
+        let parents = population_checksum(&scored_population(3).genoms);
+        let checksums: Vec<_> = [1, 2, 4]
+            .into_iter()
+            .map(|threads| {
+                let mut population = scored_population(3);
+                population.threads = threads;
+                population.next_generation(None, &mut StdRng::seed_from_u64(5));
+                population_checksum(&population.genoms)
+            })
+            .collect();
+
+        assert_ne!(checksums[0], parents);
+        assert_eq!(checksums[1], checksums[0]);
+        assert_eq!(checksums[2], checksums[0]);
+    }
+
+    /// Evolves 64 random genoms towards a random target genom for `generations` generations, each
+    /// genom scored by its similarity to the target. Returns the mean similarity in the end.
+    fn similarity_to_target(selection: SelectionStrategy, generations: usize) -> f32 {
+        let mut rng = StdRng::seed_from_u64(1);
+        let target: Genom<AndiN, AndiS> = Genom::random(8, &mut rng);
+        let mut population = Population::random(64, 8, 100, &mut rng);
+        // every genom is a parent, so the selection strategy is the only fitness pressure
+        population.parent_ratio = 1.0;
+        population.selection = selection;
+
+        let similarities = |population: &Population<AndiN, AndiS>| -> Vec<f32> {
+            population.genoms.iter().map(|genom| Genom::similarity(genom, &target)).collect()
+        };
+        for _ in 0..generations {
+            let scores = similarities(&population);
+            for (genom, similarity) in population.genoms.iter_mut().zip(scores) {
+                genom.set_score(AndiS::new(similarity));
+            }
+            let n_elite = population.next_generation(None, &mut rng);
+            population.mutate(n_elite, &mut rng);
+        }
+        similarities(&population).iter().sum::<f32>() / population.len() as f32
+    }
+
+    #[test]
+    fn tournament_selection_converges_faster_than_random_pairing() {
+        let tournament = similarity_to_target(SelectionStrategy::Tournament { size: 4 }, 20);
+        let random = similarity_to_target(SelectionStrategy::RandomPairing, 20);
+        assert!(tournament > random, "tournament {}, random pairing {}", tournament, random);
+    }
+
+    #[test]
+    fn the_elite_survives_unchanged() {
+        let mut rng = StdRng::seed_from_u64(2);
+        let mut population = scored_population(4);
+        population.n_elite = 1;
+        let champion = population.genoms[10].nucleotides.clone();
+
+        for generation in 0..10 {
+            for (i, genom) in population.genoms.iter_mut().enumerate() {
+                let score = if genom.nucleotides == champion { 1000.0 } else { i as f32 };
+                genom.set_score(AndiS::new(score));
+            }
+            let n_elite = population.next_generation(None, &mut rng);
+            population.mutate(n_elite, &mut rng);
+
+            assert_eq!(n_elite, 1);
+            assert_eq!(population.genoms[0].nucleotides, champion, "generation {}", generation);
+        }
+    }
+
+    #[test]
+    fn the_best_scoring_half_breeds_the_next_generation() {
+        let mut population = scored_population(4);
+        let parents = population.select_parents(None);
+        assert_eq!(parents, (32..64).rev().collect::<Vec<_>>());
+
+        let before = population.genoms.clone();
+        population.next_generation(None, &mut StdRng::seed_from_u64(6));
+        assert_eq!(population.len(), 64);
+        // every nucleotide of a child is the one of a parent at the same position
+        for child in &population.genoms {
+            for (j, nucl) in child.nucleotides.iter().enumerate() {
+                assert!(parents.iter().any(|&i| before[i].nucleotides[j] == *nucl));
+            }
+        }
+    }
+
+    #[test]
+    fn unchanged_elites_keep_their_scores() {
+        let mut rng = StdRng::seed_from_u64(3);
+        let mut population = scored_population(5);
+        population.n_elite = 2;
+        population.keep_elite_scores = true;
+
+        let n_elite = population.next_generation(None, &mut rng);
+        population.mutate(n_elite, &mut rng);
+        let scores: Vec<_> = population.genoms[..2].iter().map(Genom::score).collect();
+        assert_eq!(scores, [63.0, 62.0]);
+        assert!(population.genoms[2..].iter().all(|genom| !genom.has_score()));
+
+        // a changed genom has to be scored again
+        let elite = &mut population.genoms[0];
+        elite.mutate(&mut rng);
+        assert!(!elite.has_score());
+        assert_eq!(elite.score(), 0.0);
+
+        // without keeping the scores even the elites are scored again
+        let mut population = scored_population(5);
+        population.n_elite = 2;
+        population.next_generation(None, &mut rng);
+        assert!(population.genoms.iter().all(|genom| !genom.has_score()));
+    }
+
+    /// keeps the records logged on each thread, so that tests running in parallel don't see each
+    /// other's records
+    struct CapturingLogger;
+
+    thread_local! {
+        static RECORDS: std::cell::RefCell<Vec<(log::Level, String)>> = Default::default();
+    }
+
+    impl log::Log for CapturingLogger {
+        fn enabled(&self, _: &log::Metadata) -> bool {
+            true
+        }
+
+        fn log(&self, record: &log::Record) {
+            let message = (record.level(), record.args().to_string());
+            RECORDS.with(|records| records.borrow_mut().push(message));
+        }
+
+        fn flush(&self) {}
+    }
+
+    #[test]
+    fn a_generation_boundary_logs_the_surviving_parents() {
+        // a logger can only be installed once per process, later calls fail harmlessly
+        let _ = log::set_logger(&CapturingLogger);
+        log::set_max_level(log::LevelFilter::Trace);
+
+        let mut population = scored_population(6);
+        population.next_generation(None, &mut StdRng::seed_from_u64(6));
+
+        let records = RECORDS.with(|records| records.take());
+        assert!(
+            records.iter().any(|(level, message)| {
+                *level == log::Level::Info && message.starts_with("surviving parents: ")
+            }),
+            "{:?}",
+            records
+        );
+    }
+
+    #[test]
+    fn children_only_inherit_from_their_own_species() {
+        use std::collections::HashSet;
+
+        let mut population = scored_population(7);
+        population.set_species(2);
+        // the (random, so distinct) nucleotides each species starts out with
+        let mut pools = [HashSet::new(), HashSet::new()];
+        for (genom, &species) in population.genoms.iter().zip(&population.species) {
+            pools[species].extend(genom.nucleotides.iter().cloned());
+        }
+        assert!(pools[0].is_disjoint(&pools[1]));
+
+        // without mutations, every nucleotide of a child is one of its parents'
+        let mut rng = StdRng::seed_from_u64(7);
+        for _ in 0..3 {
+            population.next_generation(None, &mut rng);
+            for (genom, &species) in population.genoms.iter().zip(&population.species) {
+                assert!(genom.nucleotides.iter().all(|nucl| pools[species].contains(nucl)));
+            }
+            for (i, genom) in population.genoms.iter_mut().enumerate() {
+                genom.set_score(AndiS::new(i as f32));
+            }
+        }
+        assert_eq!(population.species.iter().filter(|&&species| species == 1).count(), 32);
+    }
+}
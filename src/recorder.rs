@@ -0,0 +1,139 @@
+use std::collections::VecDeque;
+
+use crate::creature::Point2;
+
+/// number of generations a recorder keeps by default
+const DEFAULT_CAPACITY: usize = 1;
+
+// -------------------------------------------------------------------------------------------------
+// --- Recording -----------------------------------------------------------------------------------
+// -------------------------------------------------------------------------------------------------
+
+/// The positions of all creatures at every recorded step of one generation.
+///
+/// The frames are stored back to back in a single buffer, `starts` holds where each frame begins
+/// since creatures may die during the generation.
+pub struct Recording {
+    pub generation: u32,
+    starts: Vec<usize>,
+    positions: Vec<Point2>,
+}
+
+impl Recording {
+    fn new(generation: u32) -> Self {
+        Self {
+            generation,
+            starts: Vec::new(),
+            positions: Vec::new(),
+        }
+    }
+
+    fn push(&mut self, positions: &[Point2]) {
+        self.starts.push(self.positions.len());
+        self.positions.extend(positions.iter().map(|p| Point2::new(p.x, p.y)));
+    }
+
+    /// number of recorded steps
+    pub fn len(&self) -> usize {
+        self.starts.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.starts.is_empty()
+    }
+
+    /// the positions at `step`, an empty slice if the step hasn't been recorded
+    pub fn frame(&self, step: usize) -> &[Point2] {
+        let start = match self.starts.get(step) {
+            Some(&start) => start,
+            None => return &[],
+        };
+        let end = self.starts.get(step + 1).copied().unwrap_or(self.positions.len());
+        &self.positions[start..end]
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+// --- Recorder ------------------------------------------------------------------------------------
+// -------------------------------------------------------------------------------------------------
+
+/// Records the creature positions step by step so a generation can be played back later.
+///
+/// Only the last `capacity` generations are kept, one by default, so memory stays bounded.
+pub struct Recorder {
+    enabled: bool,
+    capacity: usize,
+    recordings: VecDeque<Recording>,
+}
+
+impl Default for Recorder {
+    fn default() -> Self {
+        Self::new(DEFAULT_CAPACITY)
+    }
+}
+
+impl Recorder {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            enabled: false,
+            capacity,
+            recordings: VecDeque::with_capacity(capacity),
+        }
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.enabled
+    }
+
+    pub fn start(&mut self) {
+        self.enabled = true;
+    }
+
+    /// stops recording, the recordings made so far are kept for playback
+    pub fn stop(&mut self) {
+        self.enabled = false;
+    }
+
+    /// appends the positions of `generation`'s next step, starting a new recording (and dropping
+    /// the oldest one if full) when the generation changes. Does nothing while stopped.
+    pub fn record(&mut self, generation: u32, positions: &[Point2]) {
+        if !self.enabled || self.capacity == 0 {
+            return;
+        }
+        if self.recordings.back().map(|r| r.generation) != Some(generation) {
+            while self.recordings.len() >= self.capacity {
+                self.recordings.pop_front();
+            }
+            self.recordings.push_back(Recording::new(generation));
+        }
+        if let Some(recording) = self.recordings.back_mut() {
+            recording.push(positions);
+        }
+    }
+
+    pub fn recording(&self, generation: u32) -> Option<&Recording> {
+        self.recordings.iter().find(|r| r.generation == generation)
+    }
+
+    /// the positions at `step` of `generation`, an empty slice if that hasn't been recorded
+    pub fn frame(&self, generation: u32, step: usize) -> &[Point2] {
+        self.recording(generation).map_or(&[], |r| r.frame(step))
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// changes the number of generations kept, dropping the oldest recordings if there are too
+    /// many
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity;
+        while self.recordings.len() > capacity {
+            self.recordings.pop_front();
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.recordings.clear();
+    }
+}
@@ -0,0 +1,171 @@
+use std::time::Instant;
+
+use egui::{Color32, CtxRef, Key, TextureId};
+use egui_sdl2_gl::{painter::Painter, DpiScaling, EguiStateHandler};
+use sdl2::{
+    event::Event,
+    keyboard::Keycode,
+    video::{GLContext, Window},
+    VideoSubsystem,
+};
+
+use brains::ui::{Backend, FrameTimes};
+
+// -------------------------------------------------------------------------------------------------
+// --- SDL Backend ---------------------------------------------------------------------------------
+// -------------------------------------------------------------------------------------------------
+
+/// the [`Backend`] for the desktop: an SDL2 window painted with OpenGL
+pub struct SdlBackend {
+    pub egui_state: EguiStateHandler,
+    pub egui_ctx: CtxRef,
+    /// SDL shuts down once this is dropped
+    _sdl_context: sdl2::Sdl,
+    pub event_pump: sdl2::EventPump,
+    _video_subsystem: VideoSubsystem,
+    pub srgba: Vec<Color32>,
+    pub tex_id: TextureId,
+    /// `(width, height)` of the texture, `srgba` holds `width * height` pixels
+    pub texture_size: (usize, usize),
+    pub starttime: Instant,
+    pub painter: Painter,
+    pub window: Window,
+    /// the OpenGL context the painter draws into, it has to live as long as the window
+    _gl_ctx: GLContext,
+    pub frame_time: u32,
+    /// keys pressed during the last frame
+    pub keys_pressed: Vec<Key>,
+    /// durations of the last frames, see [`brains::ui::draw_frame_times`]
+    pub frame_times: FrameTimes,
+    last_frame: Instant,
+}
+
+impl Backend for SdlBackend {
+    fn new(
+        window_width: u32,
+        window_height: u32,
+        board_width: u32,
+        board_height: u32,
+        frame_time: u32,
+    ) -> Self {
+        let sdl_context = sdl2::init().unwrap();
+        let video_subsystem = sdl_context.video().unwrap();
+        let gl_attr = video_subsystem.gl_attr();
+        gl_attr.set_context_profile(sdl2::video::GLProfile::Core);
+
+        gl_attr.set_double_buffer(true);
+        gl_attr.set_multisample_samples(4);
+        gl_attr.set_framebuffer_srgb_compatible(true);
+        gl_attr.set_context_version(3, 2);
+
+        let window = video_subsystem
+            .window("Brains", window_width, window_height)
+            .opengl()
+            .resizable()
+            .build()
+            .unwrap();
+
+        let gl_ctx = window.gl_create_context().unwrap();
+
+        let (mut painter, egui_state) = egui_sdl2_gl::with_sdl2(&window, DpiScaling::Default);
+        let event_pump = sdl_context.event_pump().unwrap();
+        // the texture is as large as the board, whatever its aspect ratio, row by row like `srgba`
+        let texture_size = (board_width as usize, board_height as usize);
+        let srgba = vec![Color32::BLACK; texture_size.0 * texture_size.1];
+        let tex_id = painter.new_user_texture(texture_size, &srgba, false);
+
+        Self {
+            egui_state,
+            egui_ctx: CtxRef::default(),
+            _sdl_context: sdl_context,
+            event_pump,
+            _video_subsystem: video_subsystem,
+            srgba,
+            tex_id,
+            texture_size,
+            starttime: Instant::now(),
+            painter,
+            window,
+            _gl_ctx: gl_ctx,
+            frame_time,
+            keys_pressed: Vec::new(),
+            frame_times: FrameTimes::default(),
+            last_frame: Instant::now(),
+        }
+    }
+
+    fn begin_frame(&mut self) {
+        self.frame_times.push(self.last_frame.elapsed().as_secs_f32());
+        self.last_frame = Instant::now();
+        self.keys_pressed.clear();
+        self.egui_state.input.time = Some(self.starttime.elapsed().as_secs_f64());
+        self.egui_ctx.begin_frame(self.egui_state.input.take());
+    }
+
+    fn end_frame(&mut self) -> bool {
+        let (egui_output, paint_cmds) = self.egui_ctx.end_frame();
+        self.egui_state.process_output(&egui_output);
+        let paint_jobs = self.egui_ctx.tessellate(paint_cmds);
+        self.painter.paint_jobs(None, paint_jobs, &self.egui_ctx.texture());
+
+        self.window.gl_swap_window();
+        if !egui_output.needs_repaint {
+            if let Some(event) = self.event_pump.wait_event_timeout(self.frame_time) {
+                match event {
+                    Event::Quit { .. } => return false,
+                    _ => {
+                        if let Event::KeyDown {
+                            keycode: Some(key),
+                            repeat: false,
+                            ..
+                        } = event
+                        {
+                            self.keys_pressed.extend(egui_key(key));
+                        }
+                        self.egui_state.process_input(&self.window, event, &mut self.painter);
+                    }
+                }
+            }
+        }
+
+        true
+    }
+
+    fn ctx(&self) -> &CtxRef {
+        &self.egui_ctx
+    }
+
+    fn pixels(&mut self) -> &mut [Color32] {
+        &mut self.srgba
+    }
+
+    fn update_texture(&mut self) {
+        assert_eq!(
+            self.srgba.len(),
+            self.texture_size.0 * self.texture_size.1,
+            "pixel buffer doesn't match the texture"
+        );
+        self.painter.update_user_texture_data(self.tex_id, &self.srgba);
+    }
+
+    fn texture_id(&self) -> TextureId {
+        self.tex_id
+    }
+
+    fn frame_times(&self) -> &FrameTimes {
+        &self.frame_times
+    }
+
+    fn key_pressed(&self, key: Key) -> bool {
+        self.keys_pressed.contains(&key)
+    }
+}
+
+/// the egui key of an SDL key code, `None` for keys the simulation doesn't listen to
+fn egui_key(key: Keycode) -> Option<Key> {
+    match key {
+        Keycode::Space => Some(Key::Space),
+        Keycode::D => Some(Key::D),
+        _ => None,
+    }
+}
@@ -0,0 +1,211 @@
+use crate::creature::Point2;
+
+/// side length (in cells) of the buckets of a [`SpatialGrid`]
+const BUCKET_SIZE: i32 = 8;
+
+// -------------------------------------------------------------------------------------------------
+// --- Spatial Grid --------------------------------------------------------------------------------
+// -------------------------------------------------------------------------------------------------
+
+/// Buckets the creatures by position so that neighbour queries only look at the buckets around a
+/// position instead of at every creature.
+///
+/// It is a snapshot of the positions it has been built from, rebuild it once they have changed.
+/// The default grid is empty.
+#[derive(Default)]
+pub struct SpatialGrid {
+    width: i32,
+    height: i32,
+    wrap: bool,
+    buckets_x: i32,
+    buckets_y: i32,
+    /// the creatures of bucket `b` are `indices[starts[b]..starts[b + 1]]`
+    starts: Vec<usize>,
+    indices: Vec<usize>,
+    positions: Vec<Point2>,
+    /// where the next creature of each bucket goes while sorting
+    next: Vec<usize>,
+}
+
+impl SpatialGrid {
+    /// buckets `positions` on a `width` x `height` board, `wrap` makes the neighbourhood wrap
+    /// around the edges (toroidal board)
+    pub fn new(positions: &[Point2], width: i32, height: i32, wrap: bool) -> Self {
+        let mut grid = Self::default();
+        grid.rebuild(positions, width, height, wrap);
+        grid
+    }
+
+    /// buckets `positions` like [`SpatialGrid::new`], reusing the buffers of this grid
+    pub fn rebuild(&mut self, positions: &[Point2], width: i32, height: i32, wrap: bool) {
+        let buckets_x = (width.max(0) as u32).div_ceil(BUCKET_SIZE as u32) as i32;
+        let buckets_y = (height.max(0) as u32).div_ceil(BUCKET_SIZE as u32) as i32;
+        let bucket =
+            |pos: &Point2| (pos.x / BUCKET_SIZE + buckets_x * (pos.y / BUCKET_SIZE)) as usize;
+        self.width = width;
+        self.height = height;
+        self.wrap = wrap;
+        self.buckets_x = buckets_x;
+        self.buckets_y = buckets_y;
+
+        // counting sort of the creature indices by bucket
+        let starts = &mut self.starts;
+        starts.clear();
+        starts.resize((buckets_x * buckets_y) as usize + 1, 0);
+        for pos in positions {
+            starts[bucket(pos) + 1] += 1;
+        }
+        for b in 1..starts.len() {
+            starts[b] += starts[b - 1];
+        }
+        self.next.clone_from(starts);
+        self.indices.clear();
+        self.indices.resize(positions.len(), 0);
+        for (i, pos) in positions.iter().enumerate() {
+            let b = bucket(pos);
+            self.indices[self.next[b]] = i;
+            self.next[b] += 1;
+        }
+
+        self.positions.clear();
+        self.positions.extend(positions.iter().map(|p| Point2::new(p.x, p.y)));
+    }
+
+    /// Iterates over the creatures in the square of side `2 * radius + 1` around `pos`, not
+    /// including a creature sitting at `pos` itself. Yields `(index, dx, dy)` with the offset of
+    /// the creature from `pos`, the short way round on a wrapping board.
+    pub fn neighbor_offsets(
+        &self,
+        pos: &Point2,
+        radius: i32,
+    ) -> impl Iterator<Item = (usize, i32, i32)> + '_ {
+        let (x, y) = (pos.x, pos.y);
+        let columns = self.bucket_range(x, radius, self.buckets_x);
+        let rows = self.bucket_range(y, radius, self.buckets_y);
+
+        rows.flat_map(move |by| columns.clone().map(move |bx| (bx + self.buckets_x * by) as usize))
+            .flat_map(move |b| self.indices[self.starts[b]..self.starts[b + 1]].iter().copied())
+            .filter_map(move |i| {
+                let other = &self.positions[i];
+                let dx = self.offset(other.x - x, self.width);
+                let dy = self.offset(other.y - y, self.height);
+                let inside = (dx != 0 || dy != 0) && dx.abs() <= radius && dy.abs() <= radius;
+                inside.then_some((i, dx, dy))
+            })
+    }
+
+    /// The crowdedness around `pos` within `radius`, each neighbour weighted by its [`falloff`].
+    /// `0.0` if there is nobody around, `1.0` if every cell of the square is occupied.
+    pub fn crowd(&self, pos: &Point2, radius: i32) -> f32 {
+        let total = falloff_total(radius);
+        if total <= 0.0 {
+            return 0.0;
+        }
+        let sum: f32 = self.neighbor_offsets(pos, radius).map(|(_, dx, dy)| falloff(dx, dy)).sum();
+        sum / total
+    }
+
+    /// the buckets covering `center - radius..=center + radius` along an axis with `n` buckets
+    fn bucket_range(&self, center: i32, radius: i32, n: i32) -> impl Iterator<Item = i32> + Clone {
+        let (first, last) = (
+            (center - radius).div_euclid(BUCKET_SIZE),
+            (center + radius).div_euclid(BUCKET_SIZE),
+        );
+        let (first, last) = if !self.wrap {
+            (first.max(0), last.min(n - 1))
+        } else if last - first + 1 >= n {
+            (0, n - 1)
+        } else {
+            (first, last)
+        };
+        (first..=last).map(move |b| b.rem_euclid(n))
+    }
+
+    /// the offset `(dx, dy)` of the nearest creature within `radius` of `pos`, `None` if there is
+    /// nobody around. Ties go to the lower index.
+    pub fn nearest(&self, pos: &Point2, radius: i32) -> Option<(i32, i32)> {
+        self.neighbor_offsets(pos, radius)
+            .min_by_key(|&(i, dx, dy)| (dx * dx + dy * dy, i))
+            .map(|(_, dx, dy)| (dx, dy))
+    }
+
+    /// signed distance `d` along an axis of length `len`, the short way round on a wrapping board
+    fn offset(&self, d: i32, len: i32) -> i32 {
+        if !self.wrap {
+            return d;
+        }
+        let d = d.rem_euclid(len);
+        if d > len / 2 {
+            d - len
+        } else {
+            d
+        }
+    }
+}
+
+/// the weight of something sensed at offset `(dx, dy)`, `1 / (1 + d²)`
+pub fn falloff(dx: i32, dy: i32) -> f32 {
+    1.0 / (1.0 + (dx * dx + dy * dy) as f32)
+}
+
+/// the sum of the [`falloff`] of every cell in the square of side `2 * radius + 1`, without its
+/// center
+fn falloff_total(radius: i32) -> f32 {
+    let sum: f32 = (-radius..=radius)
+        .flat_map(|dy| (-radius..=radius).map(move |dx| falloff(dx, dy)))
+        .sum();
+    sum - falloff(0, 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// the indices of the neighbours of `pos` within `radius`, sorted
+    fn neighbors(grid: &SpatialGrid, pos: &Point2, radius: i32) -> Vec<usize> {
+        let mut indices: Vec<_> = grid.neighbor_offsets(pos, radius).map(|(i, _, _)| i).collect();
+        indices.sort_unstable();
+        indices
+    }
+
+    #[test]
+    fn neighbor_offsets_finds_the_cluster_but_not_the_far_creatures() {
+        // a cluster around (16, 16) straddling four buckets, and creatures far from it
+        let positions: Vec<_> = [(15, 15), (16, 16), (17, 15), (14, 17), (30, 30), (0, 16), (16, 2)]
+            .into_iter()
+            .map(|(x, y)| Point2::new(x, y))
+            .collect();
+        let grid = SpatialGrid::new(&positions, 32, 32, false);
+
+        assert_eq!(neighbors(&grid, &positions[1], 2), [0, 2, 3]);
+        assert_eq!(neighbors(&grid, &positions[0], 1), [1]);
+        assert!(neighbors(&grid, &positions[4], 5).is_empty());
+        let mut offsets: Vec<_> = grid.neighbor_offsets(&positions[1], 1).collect();
+        offsets.sort_unstable();
+        assert_eq!(offsets, [(0, -1, -1), (2, 1, -1)]);
+        // ties go to the lower index
+        assert_eq!(grid.nearest(&positions[1], 2), Some((-1, -1)));
+
+        // on a wrapping board the creatures across the edges are close
+        let grid = SpatialGrid::new(&positions, 32, 32, true);
+        assert!(neighbors(&grid, &positions[4], 2).is_empty());
+        assert_eq!(neighbors(&grid, &Point2::new(31, 16), 1), [5]);
+        assert_eq!(grid.nearest(&Point2::new(31, 16), 1), Some((1, 0)));
+    }
+
+    #[test]
+    fn a_neighbor_crowds_more_the_closer_it_is() {
+        let crowd = |neighbor: (i32, i32)| {
+            let positions = [Point2::new(10, 10), Point2::new(neighbor.0, neighbor.1)];
+            let grid = SpatialGrid::new(&positions, 32, 32, false);
+            grid.crowd(&positions[0], 5)
+        };
+
+        let (near, far) = (crowd((11, 10)), crowd((14, 10)));
+        assert!(far > 0.0);
+        assert!(near > far, "distance 1: {}, distance 4: {}", near, far);
+        assert!((near / far - falloff(1, 0) / falloff(4, 0)).abs() < 1e-5);
+        // out of range it isn't sensed at all
+        assert_eq!(crowd((16, 10)), 0.0);
+    }
+}
@@ -0,0 +1,151 @@
+use egui::Color32;
+use rand::Rng;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// the largest change of a color channel by a single mutation
+const COLOR_STEP: i16 = 32;
+/// a mutation scales `size` and `metabolism` by a factor of at most this (or its inverse)
+const SCALE_STEP: f32 = 1.25;
+const MIN_SCALE: f32 = 0.25;
+const MAX_SCALE: f32 = 4.0;
+
+// -------------------------------------------------------------------------------------------------
+// --- Traits --------------------------------------------------------------------------------------
+// -------------------------------------------------------------------------------------------------
+
+/// Heritable properties of a creature that aren't part of its brain. They are passed on to the
+/// children alongside the brain genom, but cross over and mutate on their own.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Traits {
+    /// the color the creature is drawn in, see [`crate::world::ColorMode::Traits`]
+    pub color: [u8; 3],
+    /// relative body size, `1.0` is normal
+    pub size: f32,
+    /// relative energy turnover, `1.0` is normal
+    pub metabolism: f32,
+}
+
+impl Default for Traits {
+    fn default() -> Self {
+        Self {
+            color: [128, 128, 128],
+            size: 1.0,
+            metabolism: 1.0,
+        }
+    }
+}
+
+impl Traits {
+    /// a random color, size and metabolism are normal
+    pub fn random<R: Rng>(rng: &mut R) -> Self {
+        Self {
+            color: rng.gen(),
+            ..Self::default()
+        }
+    }
+
+    /// each trait (and each color channel) comes from either parent with equal probability
+    pub fn crossover<R: Rng>(a: &Self, b: &Self, rng: &mut R) -> Self {
+        Self {
+            color: [
+                either(a.color[0], b.color[0], rng),
+                either(a.color[1], b.color[1], rng),
+                either(a.color[2], b.color[2], rng),
+            ],
+            size: either(a.size, b.size, rng),
+            metabolism: either(a.metabolism, b.metabolism, rng),
+        }
+    }
+
+    /// each trait (and each color channel) mutates with probability `prob`
+    pub fn mutate<R: Rng>(&mut self, prob: f64, rng: &mut R) {
+        let prob = prob.clamp(0.0, 1.0);
+        for channel in self.color.iter_mut() {
+            if rng.gen_bool(prob) {
+                let step = rng.gen_range(-COLOR_STEP..=COLOR_STEP);
+                *channel = (*channel as i16 + step).clamp(0, 255) as u8;
+            }
+        }
+        for value in [&mut self.size, &mut self.metabolism] {
+            if rng.gen_bool(prob) {
+                let factor = rng.gen_range(SCALE_STEP.recip()..=SCALE_STEP);
+                *value = (*value * factor).clamp(MIN_SCALE, MAX_SCALE);
+            }
+        }
+    }
+
+    pub fn color32(&self) -> Color32 {
+        let [r, g, b] = self.color;
+        Color32::from_rgb(r, g, b)
+    }
+}
+
+/// `a` or `b` with equal probability
+fn either<T, R: Rng>(a: T, b: T, rng: &mut R) -> T {
+    if rng.gen_bool(0.5) {
+        a
+    } else {
+        b
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        andis::{population_checksum, AndiN, AndiS},
+        population::Population,
+    };
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[test]
+    fn children_cross_over_their_parents_traits() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let a = Traits {
+            color: [0, 0, 0],
+            size: 0.5,
+            metabolism: 0.5,
+        };
+        let b = Traits {
+            color: [255, 255, 255],
+            size: 2.0,
+            metabolism: 2.0,
+        };
+        let children: Vec<_> = (0..50).map(|_| Traits::crossover(&a, &b, &mut rng)).collect();
+
+        for child in &children {
+            assert!(child.color.iter().all(|&channel| channel == 0 || channel == 255));
+            assert!(child.size == a.size || child.size == b.size);
+            assert!(child.metabolism == a.metabolism || child.metabolism == b.metabolism);
+        }
+        // neither parent passes on everything
+        assert!(children.iter().any(|child| *child != a && *child != b));
+    }
+
+    #[test]
+    fn traits_mutate_independently_of_the_brain() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let mut population: Population<AndiN, AndiS> = Population::random(20, 8, 1, &mut rng);
+        let genoms = population_checksum(&population.genoms);
+        let traits = population.traits.clone();
+
+        // brains that never mutate, traits that always do
+        population.mutation_rate = Some(0.0);
+        population.indel_rate = 0.0;
+        population.trait_mutation_rate = 1.0;
+        population.mutate(0, &mut rng);
+        assert_eq!(population_checksum(&population.genoms), genoms);
+        assert!(population.traits.iter().zip(&traits).all(|(mutated, old)| mutated != old));
+
+        // and the other way around
+        let genoms = population_checksum(&population.genoms);
+        let traits = population.traits.clone();
+        population.mutation_rate = Some(1.0);
+        population.trait_mutation_rate = 0.0;
+        population.mutate(0, &mut rng);
+        assert_ne!(population_checksum(&population.genoms), genoms);
+        assert_eq!(population.traits, traits);
+    }
+}
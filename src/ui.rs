@@ -1,98 +1,119 @@
-use std::time::Instant;
-
-use egui::{Color32, CtxRef, TextureId};
-use sdl2::{VideoSubsystem, event::Event, rect::Rect, render::Canvas, sys::SDL_GLattr, video::{GLContext, gl_attr::GLAttr}};
-use egui_sdl2_gl::{DpiScaling, EguiStateHandler, painter::Painter};
-use sdl2::video::{SwapInterval, Window};
-
-pub struct BoardWindow {
-    pub event_pump: sdl2::EventPump,
-    pub video_subsystem: VideoSubsystem,
-    pub srgba: Vec<Color32>,
-    pub tex_id: TextureId,
-    pub canvas: Canvas<Window>,
+use std::collections::VecDeque;
+
+use egui::{
+    vec2, Align2, Color32, CtxRef, Key, Pos2, Sense, Shape, Stroke, TextStyle, TextureId, Ui,
+};
+
+// -------------------------------------------------------------------------------------------------
+// --- Backend -------------------------------------------------------------------------------------
+// -------------------------------------------------------------------------------------------------
+
+/// The window the GUI runs in: it pumps the events into egui, paints its output and holds the
+/// texture the board is drawn into.
+///
+/// The simulation only talks to egui through this, so another windowing system (e.g. a browser
+/// canvas) only needs another implementation. `main` picks one at compile time, the SDL one is
+/// `SdlBackend` of the binary, behind the `desktop` feature.
+pub trait Backend {
+    /// opens a window and a `board_width` x `board_height` texture for the board. `frame_time` is
+    /// how long (in ms) to wait for events if egui doesn't need a repaint.
+    fn new(
+        window_width: u32,
+        window_height: u32,
+        board_width: u32,
+        board_height: u32,
+        frame_time: u32,
+    ) -> Self
+    where
+        Self: Sized;
+
+    /// starts a frame, call this before any egui panels are added
+    fn begin_frame(&mut self);
+
+    /// paints the frame and handles the events, returns `false` once the window has been closed
+    fn end_frame(&mut self) -> bool;
+
+    fn ctx(&self) -> &CtxRef;
+
+    /// the pixels of the board texture, row by row, see [`Backend::update_texture`]
+    fn pixels(&mut self) -> &mut [Color32];
+
+    /// uploads the pixels to the texture
+    fn update_texture(&mut self);
+
+    fn texture_id(&self) -> TextureId;
+
+    /// durations of the last frames, see [`draw_frame_times`]
+    fn frame_times(&self) -> &FrameTimes;
+
+    /// returns `true` if `key` has been pressed during the last frame
+    fn key_pressed(&self, key: Key) -> bool;
 }
 
+// -------------------------------------------------------------------------------------------------
+// --- Frame Times ---------------------------------------------------------------------------------
+// -------------------------------------------------------------------------------------------------
 
+/// number of frames shown by [`draw_frame_times`]
+const FRAME_TIMES_LEN: usize = 200;
 
-pub fn setup_gui(window_width: u32, window_height: u32, board_width: u32, board_height: u32, frame_time: u32) -> Egui_Ctx {
-    // SDL setup (TODO: refactor as soon as possible)
-    let sdl_context = sdl2::init().unwrap();
-    let video_subsystem = sdl_context.video().unwrap();
-    let gl_attr = video_subsystem.gl_attr();
-    gl_attr.set_context_profile(sdl2::video::GLProfile::Core);
+/// height of the graph drawn by [`draw_frame_times`]
+const FRAME_GRAPH_HEIGHT: f32 = 40.0;
 
-    gl_attr.set_double_buffer(true);
-    gl_attr.set_multisample_samples(4);
-    gl_attr.set_framebuffer_srgb_compatible(true);
-    gl_attr.set_context_version(3, 2);
-
-
-    let window = video_subsystem
-        .window("Brains", window_width, window_height)
-        .opengl().resizable().build().unwrap();
-
-    let _ctx = window.gl_create_context().unwrap();
-    //window.subsystem().gl_set_swap_interval(SwapInterval::VSync).unwrap();
-
-
-    let (mut painter, egui_state): (Painter, EguiStateHandler) = egui_sdl2_gl::with_sdl2(&window, DpiScaling::Default);
-    let egui_ctx = egui::CtxRef::default();
-    let event_pump: sdl2::EventPump = sdl_context.event_pump().unwrap();
-    let kb = sdl_context.keyboard();
-    let srgba: Vec<Color32> = (0..board_width*board_height).into_iter().map(|_| Color32::BLACK).collect();
-
-    let tex_id = painter.new_user_texture((board_width as usize, board_height as usize), &srgba, false);
-
-
-    Egui_Ctx { egui_state, egui_ctx, sdl_context, event_pump, video_subsystem, srgba, tex_id, painter, starttime: Instant::now(),
-               window, gl_ctx: _ctx, frame_time }
+/// the durations (in seconds) of the last `FRAME_TIMES_LEN` frames, oldest first
+pub struct FrameTimes {
+    times: VecDeque<f32>,
 }
 
-pub struct Egui_Ctx {
-    pub egui_state: EguiStateHandler,
-    pub egui_ctx: CtxRef,
-    pub sdl_context: sdl2::Sdl,
-    pub event_pump: sdl2::EventPump,
-    pub video_subsystem: VideoSubsystem,
-    pub srgba: Vec<Color32>,
-    pub tex_id: TextureId,
-    pub starttime: Instant,
-    pub painter: Painter,
-    pub window: Window,
-    pub gl_ctx: GLContext,
-    pub frame_time: u32,
+impl Default for FrameTimes {
+    fn default() -> Self {
+        Self {
+            times: VecDeque::with_capacity(FRAME_TIMES_LEN),
+        }
+    }
 }
 
-impl Egui_Ctx {
-    pub fn begin_frame(&mut self) {
-        self.egui_state.input.time = Some(self.starttime.elapsed().as_secs_f64());
-        self.egui_ctx.begin_frame(self.egui_state.input.take());
+impl FrameTimes {
+    /// adds the duration of a frame, dropping the oldest one if full
+    pub fn push(&mut self, seconds: f32) {
+        if self.times.len() == FRAME_TIMES_LEN {
+            self.times.pop_front();
+        }
+        self.times.push_back(seconds);
     }
 
-    pub fn update_texture(&mut self) {
-        self.painter.update_user_texture_data(self.tex_id, &self.srgba);
+    pub fn iter(&self) -> impl Iterator<Item = f32> + '_ {
+        self.times.iter().copied()
     }
 
-    pub fn end_frame(&mut self) -> bool {
-
-        let (egui_output, paint_cmds) = self.egui_ctx.end_frame();
-        self.egui_state.process_output(&egui_output);
-        let paint_jobs = self.egui_ctx.tessellate(paint_cmds);
-        self.painter.paint_jobs(None, paint_jobs, &self.egui_ctx.texture());
-
-        self.window.gl_swap_window();
-        if !egui_output.needs_repaint {
-            if let Some(event) = self.event_pump.wait_event_timeout(self.frame_time) {
-                match event {
-                    Event::Quit{..} => {return false; },
-                    _ => {
-                        self.egui_state.process_input(&self.window, event, &mut self.painter);
-                    }
-                }
-            }
-        }
+    /// the longest frame, `0.0` if there are none
+    pub fn max(&self) -> f32 {
+        self.iter().fold(0.0, f32::max)
+    }
+}
 
-        true
+/// Draws the frame times as a line graph, scaled so the longest frame reaches the top, to spot
+/// stalls.
+pub fn draw_frame_times(ui: &mut Ui, frame_times: &FrameTimes) {
+    let (response, painter) =
+        ui.allocate_painter(vec2(FRAME_TIMES_LEN as f32, FRAME_GRAPH_HEIGHT), Sense::hover());
+    let rect = response.rect;
+    painter.rect_filled(rect, 0.0, Color32::from_gray(20));
+
+    let max = frame_times.max().max(f32::EPSILON);
+    let points: Vec<_> = frame_times
+        .iter()
+        .enumerate()
+        .map(|(i, t)| Pos2::new(rect.left() + i as f32, rect.bottom() - t / max * rect.height()))
+        .collect();
+    if points.len() > 1 {
+        painter.add(Shape::line(points, Stroke::new(1.0, Color32::LIGHT_GREEN)));
     }
+    painter.text(
+        rect.right_top(),
+        Align2::RIGHT_TOP,
+        format!("{:.1} ms", max * 1000.0),
+        TextStyle::Small,
+        Color32::GRAY,
+    );
 }
@@ -0,0 +1,14 @@
+/// Derives the seed of random number stream number `index` from `master_seed` with SplitMix64.
+/// Unlike the std hashers it gives the same seeds on every platform and Rust version, so seeded
+/// runs stay reproducible.
+pub fn stream_seed(master_seed: u64, index: u64) -> u64 {
+    splitmix64(master_seed ^ splitmix64(index))
+}
+
+/// the SplitMix64 mixing function
+fn splitmix64(x: u64) -> u64 {
+    let mut z = x.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
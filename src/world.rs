@@ -1,20 +1,259 @@
-use egui::{vec2, Align, CollapsingHeader, Color32, Label, Layout, ScrollArea, TextStyle, Ui};
-use rand::Rng;
+use egui::{
+    plot::{Legend, Line, Plot, Value, Values},
+    vec2, Align, Button, CollapsingHeader, Color32, ComboBox, DragValue, Label, Layout, ProgressBar,
+    ScrollArea, Slider, TextStyle, Ui,
+};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+#[cfg(feature = "serde")]
+use std::io::BufReader;
+use std::{
+    collections::hash_map::DefaultHasher,
+    fs::File,
+    hash::{Hash, Hasher},
+    io::{self, BufWriter, Write},
+    path::Path,
+    time::Instant,
+};
 
 use crate::{
-    creature::{Creatures, NeuronNucl},
-    genes::Nucl,
+    board::{Board, BoundaryMode},
+    brain::{draw_brain, Neuron},
+    creature::{
+        cluster_by_similarity, diversity, make_phases, make_positions, make_rngs, Activation,
+        Arbitration, Creatures, Direction, GenerationStats, NeuronNucl, PhaseTimings, Point2,
+        SpawnPattern, DIVERSITY_SAMPLE,
+    },
+    genes::{Genom, Nucl, Scorer, SelectionStrategy, Simu},
+    history::{write_csv_row, History, CSV_HEADER},
+    objective::{objectives, Objective},
+    population::ExtinctionPolicy,
+    recorder::Recorder,
+    zone::{SelectionZone, Zone},
 };
 
+/// colors used by [`World::draw`]
+pub struct DrawColors {
+    /// creatures currently inside the selection zone
+    pub survivor: Color32,
+    /// creatures currently outside of the selection zone
+    pub doomed: Color32,
+    /// background of the selection zone, `None` doesn't draw the zone at all
+    pub zone: Option<Color32>,
+    pub food: Color32,
+    /// cells are tinted towards this color by their pheromone concentration, `None` doesn't show
+    /// the pheromones
+    pub pheromone: Option<Color32>,
+    /// the color of each species in [`ColorMode::Species`], repeated if there are more species
+    pub species: Vec<Color32>,
+}
+
+impl Default for DrawColors {
+    fn default() -> Self {
+        Self {
+            survivor: Color32::DARK_GREEN,
+            doomed: Color32::from_rgb(180, 60, 60),
+            zone: Some(Color32::from_rgb(230, 245, 230)),
+            food: Color32::from_rgb(230, 150, 0),
+            pheromone: Some(Color32::from_rgb(150, 80, 200)),
+            species: vec![
+                Color32::DARK_GREEN,
+                Color32::DARK_BLUE,
+                Color32::from_rgb(180, 60, 60),
+                Color32::from_rgb(200, 120, 0),
+            ],
+        }
+    }
+}
+
+/// how [`World::draw`] colors the creatures
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ColorMode {
+    /// all creatures in the survivor color
+    Uniform,
+    /// survivor or doomed color, depending on whether the creature is inside the selection zone
+    Survivor,
+    /// a color derived from the genom, related lineages end up with the same color
+    Genome,
+    /// the color of the creature's species
+    Species,
+    /// the color inherited with the creature's [`crate::traits::Traits`]
+    Traits,
+}
+
+/// derives a color from the genom's nucleotides. Identical genoms get identical colors, the
+/// channels are kept dark enough to stand out against the white board.
+pub fn genome_color<N: Nucl + Hash, S: Scorer>(genom: &Genom<N, S>) -> Color32 {
+    let mut hasher = DefaultHasher::new();
+    genom.nucleotides.hash(&mut hasher);
+    let hash = hasher.finish();
+
+    let channel = |shift: u32| ((hash >> shift) & 0xFF) as u16 * 200 / 255;
+    Color32::from_rgb(channel(0) as u8, channel(8) as u8, channel(16) as u8)
+}
+
+/// Feedback loop on the mutation rate: while the diversity of the population is below `floor` the
+/// rate grows, otherwise it shrinks again, always staying within `min_rate..=max_rate`.
+#[derive(Debug, Clone, Copy)]
+pub struct AdaptiveMutation {
+    pub floor: f32,
+    pub min_rate: f64,
+    pub max_rate: f64,
+}
+
+/// genoms at least this similar end up in the same cluster of [`World::species_count`]
+const SPECIES_SIMILARITY: f32 = 0.9;
+
+/// factor by which [`AdaptiveMutation`] changes the mutation rate per generation
+const ADAPTIVE_MUTATION_STEP: f64 = 1.5;
+
+/// The state of the world right after a step, see [`World::step_once`]. It only carries a summary
+/// of the positions unless [`World::set_snapshot_positions`] asks for all of them.
+#[derive(Debug, Clone, Default)]
+pub struct StepSnapshot {
+    /// generation and step the next step will simulate
+    pub generation: u32,
+    pub step: u32,
+    /// `true` if this step was the last one of its generation
+    pub generation_ended: bool,
+    pub n_creatures: usize,
+    /// mean position of the creatures, `(0.0, 0.0)` if there are none
+    pub centroid: (f32, f32),
+    /// the position of every creature, empty unless requested
+    pub positions: Vec<(i32, i32)>,
+}
+
+/// A copy of what [`World::draw`] needs, taken with [`World::snapshot`]. It doesn't borrow the
+/// world and is `Send + Sync` (as long as the genoms are), so another thread can render it while
+/// the simulation goes on.
+///
+/// The selection zone tint and the pheromone trails aren't part of it.
+pub struct WorldSnapshot<N: NeuronNucl> {
+    pub generation: u32,
+    pub step: u32,
+    pub width: i32,
+    pub height: i32,
+    pub positions: Vec<Point2>,
+    /// the color of each creature, as [`World::draw`] would draw it
+    pub colors: Vec<Color32>,
+    pub food: Vec<Point2>,
+    pub obstacles: Vec<Point2>,
+    pub food_color: Color32,
+    /// the genom of the selected creature, if any
+    pub selected: Option<Genom<N, N::Scorer>>,
+}
+
+impl<N: NeuronNucl> WorldSnapshot<N> {
+    /// draws the snapshot into `pixels` like [`World::draw`], returns the number of skipped
+    /// positions
+    pub fn draw(&self, pixels: &mut [Color32]) -> usize {
+        let (width, height) = (self.width, self.height);
+        debug_assert_eq!(
+            pixels.len(),
+            (width * height) as usize,
+            "pixel buffer doesn't match the board"
+        );
+
+        for pixel in pixels.iter_mut() {
+            *pixel = Color32::WHITE;
+        }
+
+        let mut skipped = 0;
+        let mut put = |pixels: &mut [Color32], pos: &Point2, color: Color32| {
+            match pixel_index(pos, width, height, pixels) {
+                Some(pixel) => pixels[pixel] = color,
+                None => skipped += 1,
+            }
+        };
+
+        for pellet in self.food.iter() {
+            put(pixels, pellet, self.food_color);
+        }
+        for obstacle in self.obstacles.iter() {
+            put(pixels, obstacle, Color32::BLACK);
+        }
+        for (position, &color) in self.positions.iter().zip(self.colors.iter()) {
+            put(pixels, position, color);
+        }
+        skipped
+    }
+}
+
+/// What [`World::advance_generations`] reports after each generation
+#[derive(Debug, Clone, Copy, Default)]
+pub struct GenerationReport {
+    /// the generation that has just ended
+    pub generation: u32,
+    /// how many of the requested generations have run so far, including this one
+    pub completed: u32,
+    pub stats: GenerationStats,
+}
+
+/// color ramp black - red - yellow - white for `t` in `[0, 1]`
+fn heat_color(t: f32) -> Color32 {
+    let channel = |offset: f32| ((t * 3.0 - offset).clamp(0.0, 1.0) * 255.0) as u8;
+    Color32::from_rgb(channel(0.0), channel(1.0), channel(2.0))
+}
+
+/// mixes `amount` (in `[0, 1]`) of `other` into `color`
+fn blend(color: Color32, other: Color32, amount: f32) -> Color32 {
+    let mix = |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * amount.clamp(0.0, 1.0)) as u8;
+    Color32::from_rgb(
+        mix(color.r(), other.r()),
+        mix(color.g(), other.g()),
+        mix(color.b(), other.b()),
+    )
+}
+
+/// index of `pos` in a row-major pixel buffer of a `width` x `height` board, `None` if it is off
+/// the board or the buffer is too small
+fn pixel_index(pos: &Point2, width: i32, height: i32, pixels: &[Color32]) -> Option<usize> {
+    if pos.x < 0 || pos.y < 0 || pos.x >= width || pos.y >= height {
+        return None;
+    }
+    let index = (pos.x + width * pos.y) as usize;
+    (index < pixels.len()).then_some(index)
+}
+
+/// upper limit of the simulation speed slider
+const MAX_SIMS_PER_FRAME: u32 = 500;
+
+/// number of generations kept in the history by default
+const DEFAULT_HISTORY_LEN: usize = 1000;
+
 pub struct World<R: Rng, N: NeuronNucl> {
     pub creatures: Creatures<N>,
-    grid: Vec<usize>,
+    /// the creature whose brain is shown in [`World::details_ui`]
+    pub selected_creature: Option<usize>,
+    board: Board,
     rng: R,
-    width: i32,
-    height: i32,
     step: u32,
     generation: u32,
     steps_in_generation: u32,
+    zone: Box<dyn SelectionZone>,
+    simu: Option<Box<dyn Simu<N, N::Scorer>>>,
+    /// the objectives to choose from, see [`objectives`]
+    objectives: Vec<Box<dyn Objective<N>>>,
+    /// index of the selected objective, the selection zone scores the creatures if `None`
+    objective: Option<usize>,
+    last_stats: GenerationStats,
+    last_timings: PhaseTimings,
+    history: History,
+    /// how often a survivor ended a generation in each cell, row by row
+    survivor_heat: Vec<u32>,
+    show_heatmap: bool,
+    /// if set, the stats of every finished generation are appended to it
+    stats_log: Option<BufWriter<File>>,
+    adaptive_mutation: Option<AdaptiveMutation>,
+    recorder: Recorder,
+    colors: DrawColors,
+    color_mode: ColorMode,
+    paused: bool,
+    single_step: bool,
+    restart: bool,
+    sims_per_frame: u32,
+    max_speed: bool,
+    /// if set, [`World::step_once`] copies all positions into its snapshot
+    snapshot_positions: bool,
 }
 
 impl<R: Rng, N: NeuronNucl> World<R, N> {
@@ -22,53 +261,232 @@ impl<R: Rng, N: NeuronNucl> World<R, N> {
     /// Params:
     ///
     /// * `n_creatures` - number of creatures, each creature has its own genom
-    /// * `n_neurons` - number of neurons per brain
-    /// * `mutation_coeff` - mutation coefficient, a mutation happens with a probability `1/mutation_coeff`
+    /// * `genome_length` - number of nucleotides per genom (= number of synapses per brain)
+    /// * `n_internal_neurons` - number of internal neurons per brain
+    /// * `mutation_coeff` - mutation coefficient, each nucleotide mutates with a probability
+    ///   `1/mutation_coeff`
     /// * `width` - the world width
     /// * `height` - the world height
     /// * `rng` - a suitable random number generator
     ///
+    /// The creatures spawn anywhere on the board, see [`WorldBuilder`] for the other parameters.
     pub fn new(
         n_creatures: usize,
-        n_neurons: usize,
+        genome_length: usize,
+        n_internal_neurons: usize,
         mutation_coeff: usize,
         width: i32,
         height: i32,
         mut rng: R,
     ) -> Self {
-        Self {
-            creatures: Creatures::new(
-                n_creatures,
-                n_neurons,
-                mutation_coeff,
-                height,
-                width,
-                &mut rng,
-            ),
-            grid: vec![n_creatures; (height * width) as usize],
-            rng,
+        let creatures = Creatures::new(
+            n_creatures,
+            genome_length,
+            n_internal_neurons,
+            mutation_coeff,
             width,
             height,
+            SpawnPattern::default(),
+            &mut rng,
+        );
+        Self::from_creatures(creatures, width, height, rng)
+    }
+
+    /// a world of `creatures` on a fresh `width` x `height` board, all other settings default
+    fn from_creatures(creatures: Creatures<N>, width: i32, height: i32, rng: R) -> Self {
+        let mut board = Board::new(width, height);
+        board.fill(&creatures.positions);
+
+        Self {
+            creatures,
+            selected_creature: None,
+            board,
+            rng,
             step: 0,
             generation: 0,
             steps_in_generation: 300,
+            zone: Box::new(Zone::default()),
+            simu: None,
+            objectives: objectives(),
+            objective: None,
+            last_stats: GenerationStats::default(),
+            last_timings: PhaseTimings::default(),
+            history: History::new(DEFAULT_HISTORY_LEN),
+            survivor_heat: vec![0; (width * height) as usize],
+            show_heatmap: false,
+            stats_log: None,
+            adaptive_mutation: None,
+            recorder: Recorder::default(),
+            colors: DrawColors::default(),
+            color_mode: ColorMode::Survivor,
+            paused: false,
+            single_step: false,
+            restart: false,
+            sims_per_frame: 10,
+            max_speed: false,
+            snapshot_positions: false,
         }
     }
 
     // --- drawing ----------------------------------------------------------------------------------
 
-    pub fn draw(&self, pixels: &mut [Color32]) {
-        // reset to white
+    /// Draws the board into `pixels`, a row-major buffer of `width * height` pixels.
+    /// Positions off the board or outside of the buffer are skipped (and reported) instead of
+    /// writing into the wrong pixel. Returns the number of skipped positions.
+    pub fn draw(&self, pixels: &mut [Color32]) -> usize {
+        let (width, height) = (self.board.width, self.board.height);
+        debug_assert_eq!(
+            pixels.len(),
+            (width * height) as usize,
+            "pixel buffer doesn't match the board"
+        );
+
+        // reset to white, tinting the selection zone if requested
         for pixel in pixels.iter_mut() {
             *pixel = Color32::WHITE;
         }
+        if let Some(zone_color) = self.colors.zone {
+            for y in 0..height {
+                for x in 0..width {
+                    if self.zone.contains(&Point2::new(x, y), width, height) {
+                        if let Some(pixel) = pixels.get_mut((x + width * y) as usize) {
+                            *pixel = zone_color;
+                        }
+                    }
+                }
+            }
+        }
+
+        if let Some(pheromone_color) = self.colors.pheromone {
+            for (pixel, &concentration) in pixels.iter_mut().zip(self.board.pheromone()) {
+                if concentration > 0.0 {
+                    *pixel = blend(*pixel, pheromone_color, concentration);
+                }
+            }
+        }
+
+        let mut skipped = 0;
+        let mut put = |pixels: &mut [Color32], pos: &Point2, color: Color32| {
+            match pixel_index(pos, width, height, pixels) {
+                Some(pixel) => pixels[pixel] = color,
+                None => skipped += 1,
+            }
+        };
+
+        for pellet in self.board.food() {
+            put(pixels, pellet, self.colors.food);
+        }
+        for obstacle in self.board.obstacles() {
+            put(pixels, obstacle, Color32::BLACK);
+        }
+
+        for (i, position) in self.creatures.positions.iter().enumerate() {
+            put(pixels, position, self.creature_color(i));
+        }
 
-        for position in self.creatures.positions.iter() {
-            pixels[(position.x + self.width * position.y) as usize] = Color32::DARK_GREEN;
+        if skipped > 0 {
+            log::warn!("draw: skipped {} positions outside of the board", skipped);
         }
+        skipped
     }
 
-    pub fn details_ui(&self, ui: &mut Ui) {
+    /// the color creature `i` is drawn in, depending on the color mode
+    fn creature_color(&self, i: usize) -> Color32 {
+        match self.color_mode {
+            ColorMode::Uniform => self.colors.survivor,
+            ColorMode::Survivor => {
+                let position = &self.creatures.positions[i];
+                if self.zone.contains(position, self.board.width, self.board.height) {
+                    self.colors.survivor
+                } else {
+                    self.colors.doomed
+                }
+            }
+            ColorMode::Genome => genome_color(&self.creatures.genoms()[i]),
+            ColorMode::Species => {
+                let species = self.creatures.population.species[i];
+                let palette = &self.colors.species;
+                palette.get(species % palette.len().max(1)).copied().unwrap_or(Color32::BLACK)
+            }
+            ColorMode::Traits => self.creatures.population.traits[i].color32(),
+        }
+    }
+
+    /// copies what is needed to draw the current step, see [`WorldSnapshot::draw`]
+    pub fn snapshot(&self) -> WorldSnapshot<N> {
+        let copy = |points: &[Point2]| points.iter().map(|p| Point2::new(p.x, p.y)).collect();
+        WorldSnapshot {
+            generation: self.generation,
+            step: self.step,
+            width: self.board.width,
+            height: self.board.height,
+            positions: copy(&self.creatures.positions),
+            colors: (0..self.creatures.positions.len()).map(|i| self.creature_color(i)).collect(),
+            food: copy(self.board.food()),
+            obstacles: copy(self.board.obstacles()),
+            food_color: self.colors.food,
+            selected: self.selected_genom().cloned(),
+        }
+    }
+
+    /// Draws where the survivors of all past generations ended up, see [`World::draw`] for the
+    /// layout of `pixels`. Cells are colored from black (never) over red and yellow to white (the
+    /// most often).
+    pub fn draw_heatmap(&self, pixels: &mut [Color32]) {
+        debug_assert_eq!(
+            pixels.len(),
+            self.survivor_heat.len(),
+            "pixel buffer doesn't match the board"
+        );
+
+        let max = self.survivor_heat.iter().copied().max().unwrap_or(0).max(1);
+        for (pixel, &heat) in pixels.iter_mut().zip(self.survivor_heat.iter()) {
+            *pixel = heat_color(heat as f32 / max as f32);
+        }
+    }
+
+    pub fn details_ui(&mut self, ui: &mut Ui) {
+        ui.horizontal(|ui| {
+            if ui.button(if self.paused { "Play" } else { "Pause" }).clicked() {
+                self.toggle_paused();
+            }
+            if ui.add_enabled(self.paused, Button::new("Step")).clicked() {
+                self.request_single_step();
+            }
+            if ui.button("Restart").clicked() {
+                self.restart = true;
+            }
+        });
+        ui.add_enabled(
+            !self.max_speed,
+            Slider::new(&mut self.sims_per_frame, 1..=MAX_SIMS_PER_FRAME).text("steps per frame"),
+        );
+        ui.checkbox(&mut self.max_speed, "max speed (no render)");
+        ui.checkbox(&mut self.show_heatmap, "show survivor heatmap");
+        let objective_name = |i: Option<usize>| match i {
+            Some(i) => self.objectives[i].name().to_string(),
+            None => "selection zone".to_string(),
+        };
+        let mut objective = self.objective;
+        ComboBox::from_label("objective")
+            .selected_text(objective_name(objective))
+            .show_ui(ui, |ui| {
+                for i in std::iter::once(None).chain((0..self.objectives.len()).map(Some)) {
+                    ui.selectable_value(&mut objective, i, objective_name(i));
+                }
+            });
+        self.objective = objective;
+        let mut activation = self.creatures.activation;
+        ComboBox::from_label("activation")
+            .selected_text(format!("{:?}", activation))
+            .show_ui(ui, |ui| {
+                for a in Activation::ALL {
+                    ui.selectable_value(&mut activation, a, format!("{:?}", a));
+                }
+            });
+        self.creatures.activation = activation;
+        ui.separator();
+
         ui.add(
             Label::new("Simulation")
                 .text_color(Color32::LIGHT_BLUE)
@@ -89,16 +507,40 @@ impl<R: Rng, N: NeuronNucl> World<R, N> {
             ui.end_row();
 
             ui.label("board: ");
-            ui.add(Label::new(format!("{}", self.width)).strong());
+            ui.add(Label::new(format!("{}", self.board.width)).strong());
             ui.label("x");
-            ui.add(Label::new(format!("{}", self.height)).strong());
+            ui.add(Label::new(format!("{}", self.board.height)).strong());
             ui.end_row();
 
             ui.label("steps in gen: ");
             ui.add(Label::new(format!("{}", self.steps_in_generation)).strong());
+            ui.end_row();
+
+            ui.label("diversity: ");
+            ui.add(Label::new(format!("{:.2}", self.last_stats.diversity)).strong());
+            ui.end_row();
+
+            let (cx, cy) = self.centroid();
+            ui.label("centroid: ");
+            ui.add(Label::new(format!("({:.1}, {:.1})", cx, cy)).strong());
+            ui.label(", spread: ");
+            ui.add(Label::new(format!("{:.1}", self.spread())).strong());
+            ui.end_row();
+
+            let ms = |duration: std::time::Duration| duration.as_secs_f64() * 1000.0;
+            let timings = &self.last_timings;
+            ui.label("last gen (ms): ");
+            ui.add(Label::new(format!("decide {:.1}", ms(timings.decide))).strong());
+            ui.add(Label::new(format!("apply {:.1}", ms(timings.apply))).strong());
+            ui.add(Label::new(format!("end {:.1}", ms(timings.end_generation))).strong());
         });
         ui.separator();
 
+        CollapsingHeader::new("History")
+            .default_open(true)
+            .show(ui, |ui| self.history_ui(ui));
+        ui.separator();
+
         ui.add(
             Label::new("Individuums")
                 .text_color(Color32::LIGHT_BLUE)
@@ -115,29 +557,352 @@ impl<R: Rng, N: NeuronNucl> World<R, N> {
             },
         );
         ui.separator();
+
+        ui.add(
+            Label::new("Brain")
+                .text_color(Color32::LIGHT_BLUE)
+                .text_style(egui::TextStyle::Heading),
+        );
+        let n = self.creatures.population.len();
+        ui.horizontal(|ui| {
+            ui.label("creature: ");
+            let mut index = self.selected_creature.unwrap_or(0);
+            let drag = DragValue::new(&mut index).clamp_range(0..=n.saturating_sub(1));
+            if ui.add(drag).changed() {
+                self.selected_creature = Some(index);
+            }
+            if ui.button("none").clicked() {
+                self.selected_creature = None;
+            }
+        });
+        if let Some(genom) = self.selected_genom() {
+            let synapses: Vec<_> =
+                genom.iter_connections(self.creatures.n_internal_neurons).collect();
+            draw_brain(
+                ui,
+                &synapses,
+                &N::input_names(),
+                self.creatures.n_internal_neurons,
+                &N::output_names(),
+                &N::acting_outputs(),
+            );
+            if !self.creatures.activations.is_empty() {
+                ui.label("activations in the last step:");
+            }
+            for (name, &activation) in N::output_names().iter().zip(&self.creatures.activations) {
+                ui.add(
+                    ProgressBar::new((activation + 1.0) / 2.0)
+                        .text(format!("{} {:.2}", name, activation)),
+                );
+            }
+        }
+        if let Some(traits) = self.selected_creature.and_then(|i| self.creatures.traits().get(i)) {
+            ui.horizontal(|ui| {
+                ui.label("traits: ");
+                ui.add(Label::new("■").text_color(traits.color32()));
+                ui.label(format!("size {:.2}, metabolism {:.2}", traits.size, traits.metabolism));
+            });
+        }
+        if let Some(selected) = self.selected_creature {
+            CollapsingHeader::new("Edit genom")
+                .default_open(false)
+                .show(ui, |ui| self.genom_editor_ui(ui, selected));
+        }
+        ui.separator();
+    }
+
+    /// a row per nucleotide of creature `selected` to rewire its synapse by hand, the changes take
+    /// effect in the next step
+    fn genom_editor_ui(&mut self, ui: &mut Ui, selected: usize) {
+        let (inputs, outputs) = (N::input_names(), N::output_names());
+        let genom = match self.creatures.population.genoms.get_mut(selected) {
+            Some(genom) => genom,
+            None => return,
+        };
+        let index_of = |neuron: Neuron| match neuron {
+            Neuron::Input(i) | Neuron::Internal(i) | Neuron::Output(i) => i,
+        };
+        let name = |names: &[String], i: usize| names.get(i).cloned().unwrap_or_default();
+
+        for index in 0..genom.nucleotides.len() {
+            // the editor wires inputs to outputs only, so it reads the synapses that way
+            let synapse = genom.nucleotides[index].synapse(0);
+            let (input, output) = (index_of(synapse.source), index_of(synapse.sink));
+            let (mut new_input, mut new_output, mut weight) = (input, output, synapse.weight);
+            ui.horizontal(|ui| {
+                ComboBox::from_id_source(("edit input", index))
+                    .selected_text(name(&inputs, input))
+                    .show_ui(ui, |ui| {
+                        for (i, neuron) in inputs.iter().enumerate() {
+                            ui.selectable_value(&mut new_input, i, neuron);
+                        }
+                    });
+                ComboBox::from_id_source(("edit output", index))
+                    .selected_text(name(&outputs, output))
+                    .show_ui(ui, |ui| {
+                        for (i, neuron) in outputs.iter().enumerate() {
+                            ui.selectable_value(&mut new_output, i, neuron);
+                        }
+                    });
+                ui.add(Slider::new(&mut weight, -N::MAX_WEIGHT..=N::MAX_WEIGHT));
+            });
+            if (new_input, new_output, weight) != (input, output, synapse.weight) {
+                genom.set_synapse(index, new_input, new_output, weight);
+            }
+        }
+    }
+
+    /// plots the survivors and the scores of the generations in the history
+    fn history_ui(&self, ui: &mut Ui) {
+        if self.history.is_empty() {
+            ui.label("no finished generation yet");
+            return;
+        }
+        let line = |stat: fn(&GenerationStats) -> f32, name: &str, color: Color32| {
+            let series = self.history.series(stat);
+            Line::new(Values::from_values_iter(series.into_iter().map(|(x, y)| Value::new(x, y))))
+                .name(name)
+                .color(color)
+        };
+
+        ui.add(
+            Plot::new("survivors")
+                .line(line(|s| s.survivors as f32, "survivors", Color32::LIGHT_GREEN))
+                .include_y(0.0)
+                .height(100.0),
+        );
+        ui.add(
+            Plot::new("scores")
+                .line(line(|s| s.best_score, "best", Color32::GOLD))
+                .line(line(|s| s.mean_score, "mean", Color32::LIGHT_BLUE))
+                .include_y(0.0)
+                .legend(Legend::default())
+                .height(100.0),
+        );
     }
 
     // --- simulation -------------------------------------------------------------------------------
 
     pub fn simulate_until_endofgeneration(&mut self) {
-        while self.step < self.steps_in_generation {
-            N::simulate(&mut self.creatures, &mut self.rng, self.width, self.height);
-            self.step += 1;
-        }
-        self.step = 0;
-        self.generation += 1;
-        N::end_generation(&mut self.creatures, &mut self.rng, self.width, self.height);
+        while !self.advance() {}
     }
 
     pub fn simulate(&mut self) {
-        N::simulate(&mut self.creatures, &mut self.rng, self.width, self.height);
+        self.advance();
+    }
+
+    /// simulates a single step and ends the generation once `steps_in_generation` steps have been
+    /// simulated. Returns `true` if the generation has ended.
+    fn advance(&mut self) -> bool {
+        self.creatures.watched = self.selected_creature;
+        N::simulate(
+            &mut self.creatures,
+            &mut self.rng,
+            &mut self.board,
+            self.step,
+        );
+        self.creatures.age(&mut self.board);
+        // creatures may have died, the selected one has moved to another index or is gone
+        self.selected_creature = self.creatures.watched;
+        self.recorder.record(self.generation, &self.creatures.positions);
         self.step += 1;
 
-        if self.step > self.steps_in_generation {
-            self.step = 0;
-            self.generation += 1;
-            N::end_generation(&mut self.creatures, &mut self.rng, self.width, self.height);
+        if self.step >= self.steps_in_generation {
+            self.end_generation();
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Simulates a single step like [`World::simulate`] and reports where the world stands
+    /// afterwards. If the generation ended, the snapshot already shows the next one.
+    pub fn step_once(&mut self) -> StepSnapshot {
+        let generation_ended = self.advance();
+
+        let positions = &self.creatures.positions;
+        StepSnapshot {
+            generation: self.generation,
+            step: self.step,
+            generation_ended,
+            n_creatures: positions.len(),
+            centroid: self.centroid(),
+            positions: if self.snapshot_positions {
+                positions.iter().map(|pos| (pos.x, pos.y)).collect()
+            } else {
+                Vec::new()
+            },
+        }
+    }
+
+    /// whether [`World::step_once`] includes every position in its snapshot, off by default
+    pub fn set_snapshot_positions(&mut self, enabled: bool) {
+        self.snapshot_positions = enabled;
+    }
+
+    /// runs `generations` whole generations without any rendering and returns the stats of each
+    pub fn run_headless(&mut self, generations: u32) -> Vec<GenerationStats> {
+        let mut stats = Vec::with_capacity(generations as usize);
+        self.advance_generations(generations, |report| {
+            stats.push(report.stats);
+            true
+        });
+        stats
+    }
+
+    /// Runs `n` whole generations, calling `callback` after each of them. The callback returns
+    /// `false` to stop early. Returns the number of generations that have run.
+    pub fn advance_generations(
+        &mut self,
+        n: u32,
+        mut callback: impl FnMut(&GenerationReport) -> bool,
+    ) -> u32 {
+        for completed in 1..=n {
+            self.simulate_until_endofgeneration();
+            let report = GenerationReport {
+                generation: self.generation - 1,
+                completed,
+                stats: self.last_stats,
+            };
+            if !callback(&report) {
+                return completed;
+            }
         }
+        n
+    }
+
+    fn end_generation(&mut self) {
+        let (width, height) = (self.board.width, self.board.height);
+        for pos in self.creatures.positions.iter() {
+            if self.zone.contains(pos, width, height) {
+                self.survivor_heat[(pos.x + width * pos.y) as usize] += 1;
+            }
+        }
+
+        let start = Instant::now();
+        self.last_stats = N::end_generation(
+            &mut self.creatures,
+            &mut self.rng,
+            &mut self.board,
+            self.zone.as_ref(),
+            self.simu.as_deref(),
+            self.objective.map(|i| self.objectives[i].as_ref()),
+        );
+        let mut timings = std::mem::take(&mut self.creatures.timings);
+        timings.end_generation = start.elapsed();
+        self.last_timings = timings;
+        self.history.push(self.generation, self.last_stats);
+        if let Some(log) = self.stats_log.as_mut() {
+            let written = write_csv_row(log, self.generation, &self.last_stats)
+                .and_then(|_| log.flush());
+            if let Err(err) = written {
+                log::error!("could not write the stats log: {}", err);
+                self.stats_log = None;
+            }
+        }
+        if let Some(adaptive) = self.adaptive_mutation {
+            let rate = self.creatures.population.mutation_probability();
+            let rate = if self.last_stats.diversity < adaptive.floor {
+                rate * ADAPTIVE_MUTATION_STEP
+            } else {
+                rate / ADAPTIVE_MUTATION_STEP
+            };
+            let rate = rate.clamp(adaptive.min_rate, adaptive.max_rate);
+            self.creatures.population.mutation_rate = Some(rate);
+        }
+        self.step = 0;
+        self.generation += 1;
+    }
+
+    /// selects the creature at `(x, y)` for inspection, or clears the selection if the cell is
+    /// empty. Returns the selected creature.
+    pub fn select_creature_at(&mut self, x: i32, y: i32) -> Option<usize> {
+        self.selected_creature = self.board.occupant(x, y);
+        self.selected_creature
+    }
+
+    /// the genom of the selected creature, if any
+    pub fn selected_genom(&self) -> Option<&Genom<N, N::Scorer>> {
+        self.selected_creature.and_then(|i| self.creatures.genoms().get(i))
+    }
+
+    // --- recording --------------------------------------------------------------------------------
+
+    /// starts recording the positions of every step, see [`World::replay_frame`]
+    pub fn start_recording(&mut self) {
+        self.recorder.start();
+    }
+
+    pub fn stop_recording(&mut self) {
+        self.recorder.stop();
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.recorder.is_recording()
+    }
+
+    /// the recorded positions after step `step` of generation `gen`, an empty slice if that step
+    /// wasn't recorded
+    pub fn replay_frame(&self, gen: u32, step: u32) -> &[Point2] {
+        self.recorder.frame(gen, step as usize)
+    }
+
+    /// sets how many generations of recordings are kept (one by default)
+    pub fn set_recording_capacity(&mut self, generations: usize) {
+        self.recorder.set_capacity(generations);
+    }
+
+    pub fn recorder(&self) -> &Recorder {
+        &self.recorder
+    }
+
+    // --- pausing ----------------------------------------------------------------------------------
+
+    pub fn paused(&self) -> bool {
+        self.paused
+    }
+
+    pub fn set_paused(&mut self, paused: bool) {
+        self.paused = paused;
+    }
+
+    pub fn toggle_paused(&mut self) {
+        self.paused = !self.paused;
+    }
+
+    /// asks for a single step to be simulated while paused, see [`World::take_single_step`]
+    pub fn request_single_step(&mut self) {
+        self.single_step = true;
+    }
+
+    /// returns `true` (once) if a single step has been requested
+    pub fn take_single_step(&mut self) -> bool {
+        std::mem::take(&mut self.single_step)
+    }
+
+    /// returns `true` (once) if the restart button has been clicked, see [`World::reset`]
+    pub fn take_restart(&mut self) -> bool {
+        std::mem::take(&mut self.restart)
+    }
+
+    // --- speed ------------------------------------------------------------------------------------
+
+    /// number of simulation steps per rendered frame
+    pub fn sims_per_frame(&self) -> u32 {
+        self.sims_per_frame
+    }
+
+    pub fn set_sims_per_frame(&mut self, sims: u32) {
+        self.sims_per_frame = sims;
+    }
+
+    /// in max speed mode whole generations are simulated per frame and the board isn't drawn
+    pub fn max_speed(&self) -> bool {
+        self.max_speed
+    }
+
+    pub fn set_max_speed(&mut self, max_speed: bool) {
+        self.max_speed = max_speed;
     }
 
     // --- properties -------------------------------------------------------------------------------
@@ -149,7 +914,1162 @@ impl<R: Rng, N: NeuronNucl> World<R, N> {
         self.step
     }
 
+    /// mean position of the creatures, `(0.0, 0.0)` if there are none
+    pub fn centroid(&self) -> (f32, f32) {
+        let positions = &self.creatures.positions;
+        let n = positions.len().max(1) as f32;
+        let (sum_x, sum_y) = positions
+            .iter()
+            .fold((0.0, 0.0), |(x, y), pos| (x + pos.x as f32, y + pos.y as f32));
+        (sum_x / n, sum_y / n)
+    }
+
+    /// mean distance of the creatures from their [`World::centroid`], `0.0` if there are none
+    pub fn spread(&self) -> f32 {
+        let (cx, cy) = self.centroid();
+        let positions = &self.creatures.positions;
+        let total: f32 = positions
+            .iter()
+            .map(|pos| (pos.x as f32 - cx).hypot(pos.y as f32 - cy))
+            .sum();
+        total / positions.len().max(1) as f32
+    }
+
+    /// where the time of the last generation went
+    pub fn last_timings(&self) -> PhaseTimings {
+        self.last_timings
+    }
+
+    /// the number of clusters of similar genoms in the population, see [`cluster_by_similarity`]
+    pub fn species_count(&self) -> usize {
+        let clusters = cluster_by_similarity(self.creatures.genoms(), SPECIES_SIMILARITY);
+        clusters.iter().max().map_or(0, |&last| last + 1)
+    }
+
+    /// `(width, height)` of the board, the pixel buffers of [`World::draw`] have to match it
+    pub fn board_size(&self) -> (i32, i32) {
+        (self.board.width, self.board.height)
+    }
+
+    /// returns the index of the creature occupying the cell `(x, y)`, if any
+    pub fn cell_occupant(&self, x: i32, y: i32) -> Option<usize> {
+        self.board.occupant(x, y)
+    }
+
     pub fn set_steps_in_generation(&mut self, steps: u32) {
         self.steps_in_generation = steps;
     }
+
+    /// statistics of the past generations
+    pub fn history(&self) -> &History {
+        &self.history
+    }
+
+    /// sets how many generations are kept in the history
+    pub fn set_history_len(&mut self, len: usize) {
+        self.history.set_capacity(len);
+    }
+
+    /// the probability of each nucleotide of a child to mutate in the next generation
+    pub fn mutation_rate(&self) -> f64 {
+        self.creatures.population.mutation_probability()
+    }
+
+    /// Enables or disables the adaptive mutation rate. When enabled, the mutation rate rises while
+    /// the diversity is below `floor` and falls otherwise, within `min_rate..=max_rate`. When
+    /// disabled, the rate goes back to `1/mutation_coeff`.
+    ///
+    /// Enabling fails, leaving the mutation rate as it is, unless `0 <= min_rate <= max_rate <= 1`.
+    pub fn set_adaptive_mutation(
+        &mut self,
+        enabled: bool,
+        floor: f32,
+        min_rate: f64,
+        max_rate: f64,
+    ) -> Result<(), String> {
+        if !enabled {
+            self.adaptive_mutation = None;
+            self.creatures.population.mutation_rate = None;
+            return Ok(());
+        }
+        // also rejects NaN
+        if !(0.0 <= min_rate && min_rate <= max_rate && max_rate <= 1.0) {
+            return Err(format!(
+                "the mutation rates need 0 <= min_rate <= max_rate <= 1, got {} and {}",
+                min_rate, max_rate
+            ));
+        }
+        self.adaptive_mutation = Some(AdaptiveMutation {
+            floor,
+            min_rate,
+            max_rate,
+        });
+        Ok(())
+    }
+
+    /// writes the stats of all generations in the history to `path` as CSV
+    pub fn export_stats_csv<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.history.write_csv(&mut writer)?;
+        writer.flush()
+    }
+
+    /// starts appending the stats of every finished generation to a CSV file at `path`, which is
+    /// overwritten. `None` stops logging.
+    pub fn log_stats_csv<P: AsRef<Path>>(&mut self, path: Option<P>) -> io::Result<()> {
+        self.stats_log = match path {
+            Some(path) => {
+                let mut writer = BufWriter::new(File::create(path)?);
+                writeln!(writer, "{}", CSV_HEADER)?;
+                Some(writer)
+            }
+            None => None,
+        };
+        Ok(())
+    }
+
+    /// the genetic diversity of the current population, see [`diversity`]. The value of the last
+    /// finished generation is kept in the history.
+    pub fn diversity(&mut self) -> f32 {
+        diversity(self.creatures.genoms(), DIVERSITY_SAMPLE, &mut self.rng)
+    }
+
+    /// best score of the last finished generation
+    pub fn best_score(&self) -> f32 {
+        self.last_stats.best_score
+    }
+
+    /// mean score of the last finished generation
+    pub fn mean_score(&self) -> f32 {
+        self.last_stats.mean_score
+    }
+
+    /// sets the fraction of the population (the best scoring ones) that is allowed to reproduce
+    pub fn set_parent_ratio(&mut self, ratio: f32) {
+        self.creatures.population.parent_ratio = ratio;
+    }
+
+    /// sets the colors of creatures inside and outside the selection zone, as well as the color of
+    /// the zone itself (`None` to not draw it). The other colors stay as they are.
+    pub fn set_draw_colors(&mut self, survivor: Color32, doomed: Color32, zone: Option<Color32>) {
+        self.colors.survivor = survivor;
+        self.colors.doomed = doomed;
+        self.colors.zone = zone;
+    }
+
+    pub fn set_color_mode(&mut self, mode: ColorMode) {
+        self.color_mode = mode;
+    }
+
+    /// sets what happens to creatures moving off the board
+    pub fn set_boundary_mode(&mut self, mode: BoundaryMode) {
+        self.board.boundary = mode;
+    }
+
+    /// sets the number of best genoms that are copied unchanged (and unmutated) into the next
+    /// generation. Clamped to the number of parents.
+    pub fn set_elitism(&mut self, n_elite: usize) {
+        self.creatures.population.n_elite = n_elite;
+    }
+
+    /// lets the elites keep their scores, so a simulator (see [`World::set_simulator`]) doesn't
+    /// score them again
+    pub fn set_keep_elite_scores(&mut self, keep: bool) {
+        self.creatures.population.keep_elite_scores = keep;
+    }
+
+    /// sets what happens when no creature qualifies as a parent at the end of a generation
+    pub fn set_extinction_policy(&mut self, policy: ExtinctionPolicy) {
+        self.creatures.population.extinction = policy;
+    }
+
+    /// breeds the next generations on `threads` threads, `1` breeds them sequentially
+    pub fn set_breeding_threads(&mut self, threads: usize) {
+        self.creatures.population.threads = threads.max(1);
+    }
+
+    /// sets how the parents pick their mating partners
+    pub fn set_selection_strategy(&mut self, strategy: SelectionStrategy) {
+        self.creatures.population.selection = strategy;
+    }
+
+    /// scores each genom with `simu` instead of by where its creature ended up in the world
+    pub fn set_simulator<S: Simu<N, N::Scorer> + 'static>(&mut self, simu: S) {
+        self.simu = Some(Box::new(simu));
+    }
+
+    /// goes back to scoring by the creatures' positions in the world
+    pub fn clear_simulator(&mut self) {
+        self.simu = None;
+    }
+
+    /// the names of the objectives to choose from in [`World::set_objective`]
+    pub fn objective_names(&self) -> Vec<&str> {
+        self.objectives.iter().map(|o| o.name()).collect()
+    }
+
+    pub fn objective(&self) -> Option<usize> {
+        self.objective
+    }
+
+    /// scores the creatures with the objective at `index` of [`World::objective_names`] instead
+    /// of by their distance to the selection zone, `None` goes back to the zone
+    pub fn set_objective(&mut self, index: Option<usize>) {
+        assert!(index.is_none_or(|i| i < self.objectives.len()), "no such objective");
+        self.objective = index;
+    }
+
+    // --- food -------------------------------------------------------------------------------------
+
+    pub fn food(&self) -> &[Point2] {
+        self.board.food()
+    }
+
+    /// sets the number of food pellets scattered each generation and rescatters them right away
+    pub fn set_food_count(&mut self, count: usize) {
+        self.board.food_count = count;
+        self.board.scatter_food(&mut self.rng);
+    }
+
+    /// creatures older than `max_age` steps die in the middle of the generation, `None` lets them
+    /// all live until its end
+    pub fn set_max_age(&mut self, max_age: Option<u32>) {
+        self.creatures.max_age = max_age;
+    }
+
+    /// how far the creatures sense each other and pheromones, the signals fall off with the
+    /// square of the distance
+    pub fn set_sensor_range(&mut self, sensor_range: i32) {
+        self.creatures.sensor_range = sensor_range.max(0);
+    }
+
+    /// Splits the creatures into `n_species` species of equal size. They share the board but only
+    /// breed within their species, each one selected on its own.
+    ///
+    /// The species are tags on the single population (see [`crate::population::Population`])
+    /// instead of a separate `Creatures` per species, so collisions and senses between species
+    /// need no special cases.
+    pub fn set_species(&mut self, n_species: usize) {
+        self.creatures.population.set_species(n_species);
+    }
+
+    /// sets how the movement outputs of the brains decide on a move
+    pub fn set_arbitration(&mut self, arbitration: Arbitration) {
+        self.creatures.arbitration = arbitration;
+    }
+
+    /// makes `species` (see [`World::set_species`]) hunt the others, `None` lets every species
+    /// live in peace
+    pub fn set_predators(&mut self, species: Option<usize>) {
+        self.creatures.predators = species;
+    }
+
+    /// sets the activation function of the neurons
+    pub fn set_activation(&mut self, activation: Activation) {
+        self.creatures.activation = activation;
+    }
+
+    /// every creature can make `max_energy` moves per generation, `None` lets them move freely.
+    /// Refills the energy of the current generation.
+    pub fn set_max_energy(&mut self, max_energy: Option<i32>) {
+        self.creatures.max_energy = max_energy;
+        let full_energy = self.creatures.full_energy();
+        self.creatures.energy.fill(full_energy);
+    }
+
+    /// only creatures that have eaten more than `threshold` food pellets may reproduce, `None`
+    /// turns the requirement off
+    pub fn set_food_threshold(&mut self, threshold: Option<u32>) {
+        self.creatures.food_threshold = threshold;
+    }
+
+    // --- heatmap ----------------------------------------------------------------------------------
+
+    pub fn show_heatmap(&self) -> bool {
+        self.show_heatmap
+    }
+
+    pub fn set_show_heatmap(&mut self, show: bool) {
+        self.show_heatmap = show;
+    }
+
+    /// how often a survivor ended a generation in each cell, row by row
+    pub fn survivor_heat(&self) -> &[u32] {
+        &self.survivor_heat
+    }
+
+    pub fn clear_heatmap(&mut self) {
+        for heat in self.survivor_heat.iter_mut() {
+            *heat = 0;
+        }
+    }
+
+    // --- pheromones -------------------------------------------------------------------------------
+
+    /// the pheromone concentration of every cell, row by row
+    pub fn pheromone(&self) -> &[f32] {
+        self.board.pheromone()
+    }
+
+    /// sets the factor by which the pheromones decay each step, `1.0` keeps them forever
+    pub fn set_pheromone_decay(&mut self, decay: f32) {
+        self.board.pheromone_decay = decay.clamp(0.0, 1.0);
+    }
+
+    // --- obstacles --------------------------------------------------------------------------------
+
+    pub fn obstacles(&self) -> &[Point2] {
+        self.board.obstacles()
+    }
+
+    /// places an obstacle at `(x, y)`. A creature sitting there is moved to a random free cell.
+    pub fn add_obstacle(&mut self, x: i32, y: i32) {
+        let occupant = self.board.occupant(x, y);
+        if !self.board.add_obstacle(x, y) {
+            return;
+        }
+        if let Some(i) = occupant {
+            let free: Vec<_> = (0..self.board.height)
+                .flat_map(|y| (0..self.board.width).map(move |x| Point2::new(x, y)))
+                .filter(|pos| self.board.is_free(pos.x, pos.y))
+                .collect();
+            if free.is_empty() {
+                return;
+            }
+            let pos = &free[self.rng.gen_range(0..free.len())];
+            let target = Point2::new(pos.x, pos.y);
+            self.board.move_occupant(i, &Point2::new(x, y), &target);
+            self.creatures.positions[i] = target;
+        }
+    }
+
+    pub fn clear_obstacles(&mut self) {
+        self.board.clear_obstacles();
+    }
+
+    /// sets the zone creatures have to reach to survive the generation
+    pub fn set_selection_zone<Z: SelectionZone + 'static>(&mut self, zone: Z) {
+        self.zone = Box::new(zone);
+    }
+}
+
+impl<R: Rng + SeedableRng, N: NeuronNucl> World<R, N> {
+    /// Restarts the run with a fresh random population, as if the world had just been created
+    /// with `seed`. The board, the settings and the allocations are kept, the history, the
+    /// recordings and the heatmap are cleared.
+    pub fn reset(&mut self, seed: u64) {
+        self.rng = R::seed_from_u64(seed);
+        let (width, height) = (self.board.width, self.board.height);
+
+        // same order of random draws as `Creatures::new`
+        let creatures = &mut self.creatures;
+        creatures.population.randomize(&mut self.rng);
+        let n = creatures.population.len();
+        creatures.positions = make_positions(
+            n,
+            width,
+            height,
+            self.board.obstacles(),
+            &creatures.spawn,
+            &mut self.rng,
+        );
+        creatures.phases = make_phases(n, &mut self.rng);
+        creatures.rngs = make_rngs(n, self.rng.gen());
+        creatures.headings.clear();
+        creatures.headings.resize(n, (0, 0));
+        creatures.facing.clear();
+        creatures.facing.resize(n, Direction::default());
+        creatures.food_eaten.clear();
+        creatures.food_eaten.resize(n, 0);
+        creatures.ages.clear();
+        creatures.ages.resize(n, 0);
+        let full_energy = creatures.full_energy();
+        creatures.energy.clear();
+        creatures.energy.resize(n, full_energy);
+
+        self.board.fill(&creatures.positions);
+        self.board.scatter_food(&mut self.rng);
+        self.board.clear_pheromone();
+
+        self.step = 0;
+        self.generation = 0;
+        self.last_stats = GenerationStats::default();
+        self.last_timings = PhaseTimings::default();
+        self.creatures.timings = PhaseTimings::default();
+        self.history.clear();
+        self.recorder.clear();
+        self.clear_heatmap();
+        self.selected_creature = None;
+    }
+}
+
+impl<N: NeuronNucl> World<StdRng, N> {
+    /// Creates a new world with a seeded random number generator, the seed fully determines the
+    /// run. The creatures spawn uniformly, see [`World::new`] for the other parameters.
+    pub fn from_seed(
+        seed: u64,
+        n_creatures: usize,
+        genome_length: usize,
+        n_internal_neurons: usize,
+        mutation_coeff: usize,
+        width: i32,
+        height: i32,
+    ) -> Self {
+        Self::new(
+            n_creatures,
+            genome_length,
+            n_internal_neurons,
+            mutation_coeff,
+            width,
+            height,
+            StdRng::seed_from_u64(seed),
+        )
+    }
+}
+
+// --- builder ------------------------------------------------------------------------------------
+
+/// Builds a [`World`] step by step instead of through the long positional argument list of
+/// [`World::new`]. Parameters that aren't set keep their defaults.
+///
+/// ```ignore
+/// let world: World<_, AndiN> = WorldBuilder::new()
+///     .seed(42)
+///     .creatures(500)
+///     .size(256, 128)
+///     .build();
+/// ```
+pub struct WorldBuilder<R: Rng> {
+    n_creatures: usize,
+    genome_length: usize,
+    n_internal_neurons: usize,
+    mutation_coeff: usize,
+    width: i32,
+    height: i32,
+    steps_in_generation: u32,
+    zone: Box<dyn SelectionZone>,
+    spawn: SpawnPattern,
+    threads: usize,
+    rng: R,
+}
+
+impl WorldBuilder<StdRng> {
+    /// a builder with default parameters and an entropy seeded random number generator
+    pub fn new() -> Self {
+        Self {
+            n_creatures: 1000,
+            genome_length: 5,
+            n_internal_neurons: 0,
+            mutation_coeff: 1000,
+            width: 128,
+            height: 128,
+            steps_in_generation: 300,
+            zone: Box::new(Zone::default()),
+            spawn: SpawnPattern::default(),
+            threads: 1,
+            rng: StdRng::from_entropy(),
+        }
+    }
+}
+
+impl Default for WorldBuilder<StdRng> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<R: Rng> WorldBuilder<R> {
+    pub fn creatures(mut self, n: usize) -> Self {
+        self.n_creatures = n;
+        self
+    }
+
+    pub fn genome_length(mut self, n: usize) -> Self {
+        self.genome_length = n;
+        self
+    }
+
+    pub fn internal_neurons(mut self, n: usize) -> Self {
+        self.n_internal_neurons = n;
+        self
+    }
+
+    /// each nucleotide of a child mutates with a probability of `1/coeff`
+    pub fn mutation_coeff(mut self, coeff: usize) -> Self {
+        self.mutation_coeff = coeff;
+        self
+    }
+
+    pub fn size(mut self, width: i32, height: i32) -> Self {
+        self.width = width;
+        self.height = height;
+        self
+    }
+
+    pub fn steps_in_generation(mut self, steps: u32) -> Self {
+        self.steps_in_generation = steps;
+        self
+    }
+
+    /// the zone creatures have to reach to survive the generation
+    pub fn zone<Z: SelectionZone + 'static>(mut self, zone: Z) -> Self {
+        self.zone = Box::new(zone);
+        self
+    }
+
+    /// where the creatures are placed at the start of each generation
+    pub fn spawn(mut self, spawn: SpawnPattern) -> Self {
+        self.spawn = spawn;
+        self
+    }
+
+    /// breeds the next generations on `threads` threads, see [`World::set_breeding_threads`]
+    pub fn threads(mut self, threads: usize) -> Self {
+        self.threads = threads;
+        self
+    }
+
+    /// uses `rng` as random number generator of the world
+    pub fn rng<R2: Rng>(self, rng: R2) -> WorldBuilder<R2> {
+        WorldBuilder {
+            n_creatures: self.n_creatures,
+            genome_length: self.genome_length,
+            n_internal_neurons: self.n_internal_neurons,
+            mutation_coeff: self.mutation_coeff,
+            width: self.width,
+            height: self.height,
+            steps_in_generation: self.steps_in_generation,
+            zone: self.zone,
+            spawn: self.spawn,
+            threads: self.threads,
+            rng,
+        }
+    }
+
+    /// uses a [`StdRng`] seeded with `seed`, the seed fully determines the run
+    pub fn seed(self, seed: u64) -> WorldBuilder<StdRng> {
+        self.rng(StdRng::seed_from_u64(seed))
+    }
+
+    pub fn build<N: NeuronNucl>(mut self) -> World<R, N> {
+        let creatures = Creatures::new(
+            self.n_creatures,
+            self.genome_length,
+            self.n_internal_neurons,
+            self.mutation_coeff,
+            self.width,
+            self.height,
+            self.spawn,
+            &mut self.rng,
+        );
+        let mut world = World::from_creatures(creatures, self.width, self.height, self.rng);
+        world.set_steps_in_generation(self.steps_in_generation);
+        world.set_breeding_threads(self.threads);
+        world.zone = self.zone;
+        world
+    }
+}
+
+// --- persistence ----------------------------------------------------------------------------------
+
+#[cfg(feature = "serde")]
+impl<R, N> World<R, N>
+where
+    R: Rng,
+    N: NeuronNucl + serde::Serialize + serde::de::DeserializeOwned,
+{
+    /// writes the genoms of the whole population to `path` as JSON
+    pub fn save_population<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let writer = BufWriter::new(File::create(path)?);
+        serde_json::to_writer(writer, self.creatures.genoms())?;
+        Ok(())
+    }
+
+    /// replaces the population by the genoms stored in `path` (see [`World::save_population`]).
+    /// The creatures are respawned and a fresh generation is started.
+    pub fn load_population<P: AsRef<Path>>(&mut self, path: P) -> io::Result<()> {
+        let reader = BufReader::new(File::open(path)?);
+        let genoms: Vec<Genom<N, N::Scorer>> = serde_json::from_reader(reader)?;
+
+        self.creatures.positions = make_positions(
+            genoms.len(),
+            self.board.width,
+            self.board.height,
+            self.board.obstacles(),
+            &self.creatures.spawn,
+            &mut self.rng,
+        );
+        let n = genoms.len();
+        self.creatures.population.genoms = genoms;
+        let n_species = self.creatures.population.n_species;
+        self.creatures.population.set_species(n_species);
+        self.creatures.population.randomize_traits(&mut self.rng);
+        self.creatures.phases = make_phases(n, &mut self.rng);
+        self.creatures.rngs = make_rngs(n, self.rng.gen());
+        self.creatures.headings = vec![(0, 0); n];
+        self.creatures.facing = vec![Direction::default(); n];
+        self.creatures.food_eaten = vec![0; n];
+        self.creatures.ages = vec![0; n];
+        self.creatures.energy = vec![self.creatures.full_energy(); n];
+        self.creatures.population.size = n;
+        self.board.fill(&self.creatures.positions);
+        self.step = 0;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::andis::{population_checksum, AndiN, AndiS};
+    use std::time::Duration;
+
+    /// Population checksum at the end of [`golden_run`]. It changes with every change of the
+    /// simulation's behavior: if the change is intended, run `cargo test golden_run` and copy the
+    /// new checksum from the failure message.
+    const GOLDEN_CHECKSUM: u64 = 0x7abb_572e_169b_8a50;
+
+    #[test]
+    fn golden_run() {
+        let mut world: World<StdRng, AndiN> = WorldBuilder::new()
+            .creatures(200)
+            .genome_length(8)
+            .size(64, 64)
+            .steps_in_generation(50)
+            .seed(1234)
+            .build();
+        assert_eq!(world.run_headless(5).len(), 5);
+
+        let checksum = population_checksum(world.creatures.genoms());
+        assert_eq!(
+            checksum, GOLDEN_CHECKSUM,
+            "the golden run has changed, its checksum is now {:#018x}",
+            checksum
+        );
+    }
+
+    /// a small world with short generations, the same one for the same `seed`
+    fn small_world(seed: u64) -> World<StdRng, AndiN> {
+        WorldBuilder::new()
+            .creatures(100)
+            .genome_length(8)
+            .size(32, 32)
+            .steps_in_generation(10)
+            .seed(seed)
+            .build()
+    }
+
+    /// a small seeded world whose creatures all have the same genom
+    fn converged_world() -> World<StdRng, AndiN> {
+        let mut world = small_world(7);
+        let genom = world.creatures.genoms()[0].clone();
+        for other in world.creatures.population.genoms.iter_mut() {
+            *other = genom.clone();
+        }
+        world
+    }
+
+    #[test]
+    fn adaptive_mutation_rises_in_a_converged_population() {
+        let mut world = converged_world();
+        world.set_adaptive_mutation(true, 0.5, 0.0001, 0.5).unwrap();
+
+        let mut rates = vec![world.mutation_rate()];
+        for _ in 0..3 {
+            world.run_headless(1);
+            rates.push(world.mutation_rate());
+        }
+        assert!(rates.windows(2).all(|pair| pair[1] > pair[0]), "rates: {:?}", rates);
+        assert!(rates.iter().all(|&rate| rate <= 0.5));
+    }
+
+    #[test]
+    fn adaptive_mutation_rejects_bad_rates() {
+        let mut world = converged_world();
+        let rate = world.mutation_rate();
+        let bad_rates = [(0.2, 0.1), (-0.1, 0.5), (0.1, 1.5), (f64::NAN, 0.5), (0.1, f64::NAN)];
+        for (min_rate, max_rate) in bad_rates {
+            assert!(world.set_adaptive_mutation(true, 0.5, min_rate, max_rate).is_err());
+        }
+        world.run_headless(1);
+        assert_eq!(world.mutation_rate(), rate);
+
+        assert!(world.set_adaptive_mutation(true, 0.5, 0.0, 1.0).is_ok());
+        assert!(world.set_adaptive_mutation(false, 0.5, f64::NAN, f64::NAN).is_ok());
+    }
+
+    #[test]
+    fn set_draw_colors_keeps_the_other_colors() {
+        let mut world = converged_world();
+        world.colors.food = Color32::WHITE;
+        world.colors.species = vec![Color32::BLACK];
+        world.set_draw_colors(Color32::GREEN, Color32::RED, None);
+
+        assert_eq!(world.colors.survivor, Color32::GREEN);
+        assert_eq!(world.colors.doomed, Color32::RED);
+        assert_eq!(world.colors.zone, None);
+        assert_eq!(world.colors.food, Color32::WHITE);
+        assert_eq!(world.colors.species, [Color32::BLACK]);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn saved_populations_load_back_unchanged() {
+        let mut world = small_world(3);
+        world.run_headless(100);
+        let path = std::env::temp_dir().join(format!("brains-{}.json", std::process::id()));
+        world.save_population(&path).unwrap();
+
+        let mut fresh = small_world(4);
+        fresh.load_population(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        let nucleotides = |world: &World<StdRng, AndiN>| -> Vec<_> {
+            world.creatures.genoms().iter().map(|genom| genom.nucleotides.clone()).collect()
+        };
+        assert_eq!(nucleotides(&fresh), nucleotides(&world));
+        assert_eq!(fresh.step(), 0);
+    }
+
+    #[test]
+    fn mean_score_rises_over_the_generations() {
+        let mut world = small_world(5);
+        world.set_steps_in_generation(30);
+        let stats = world.run_headless(30);
+        let mean = |stats: &[GenerationStats]| {
+            stats.iter().map(|stats| stats.mean_score).sum::<f32>() / stats.len() as f32
+        };
+
+        assert!(
+            mean(&stats[25..]) > mean(&stats[..5]),
+            "mean scores: {:?}",
+            stats.iter().map(|stats| stats.mean_score).collect::<Vec<_>>()
+        );
+        assert_eq!(world.mean_score(), stats[29].mean_score);
+        assert_eq!(world.best_score(), stats[29].best_score);
+    }
+
+    #[test]
+    fn genome_color_tells_genoms_apart() {
+        let world = small_world(6);
+        let genom = &world.creatures.genoms()[0];
+        let mut mutated = genom.clone();
+        mutated.nucleotides[3].mutate_bit(0);
+
+        assert_eq!(genome_color(genom), genome_color(&genom.clone()));
+        assert_ne!(genome_color(genom), genome_color(&mutated));
+    }
+
+    #[test]
+    fn speed_settings_read_back() {
+        let mut world = small_world(0);
+        world.set_sims_per_frame(200);
+        world.set_max_speed(true);
+
+        assert_eq!(world.sims_per_frame(), 200);
+        assert!(world.max_speed());
+    }
+
+    #[test]
+    fn stepping_and_fast_forwarding_end_the_generation_alike() {
+        let mut fast = small_world(8);
+        fast.simulate_until_endofgeneration();
+        let mut stepped = small_world(8);
+        for _ in 0..10 {
+            stepped.simulate();
+        }
+
+        assert_eq!((fast.generation(), fast.step()), (1, 0));
+        assert_eq!((stepped.generation(), stepped.step()), (1, 0));
+        assert_eq!(
+            population_checksum(stepped.creatures.genoms()),
+            population_checksum(fast.creatures.genoms())
+        );
+    }
+
+    #[test]
+    fn worlds_from_the_same_seed_evolve_alike() {
+        let run = |seed: u64| {
+            let mut world: World<StdRng, AndiN> = World::from_seed(seed, 100, 8, 0, 100, 32, 32);
+            world.set_steps_in_generation(10);
+            world.run_headless(20);
+            world.creatures.genoms().iter().map(Genom::to_hex_string).collect::<Vec<_>>()
+        };
+
+        assert_eq!(run(11), run(11));
+        assert_ne!(run(11), run(12));
+    }
+
+    #[test]
+    fn headless_runs_count_the_generations() {
+        let mut world = small_world(1);
+        let stats = world.run_headless(5);
+
+        assert_eq!(world.generation(), 5);
+        assert_eq!(world.step(), 0);
+        assert_eq!(stats.len(), 5);
+    }
+
+    /// a simulator giving every genom the same score
+    #[derive(Debug)]
+    struct FixedScore(f32);
+
+    impl Simu<AndiN, AndiS> for FixedScore {
+        fn simulate(&self, genom: &mut Genom<AndiN, AndiS>) {
+            genom.set_score(AndiS::new(self.0));
+        }
+    }
+
+    #[test]
+    fn a_simulator_scores_the_genoms() {
+        let mut world = small_world(2);
+        let mut genom = world.creatures.genoms()[0].clone();
+        FixedScore(0.25).simulate(&mut genom);
+        assert_eq!(genom.score(), 0.25);
+
+        world.set_simulator(FixedScore(0.25));
+        world.simulate_until_endofgeneration();
+        assert_eq!(world.best_score(), 0.25);
+        assert_eq!(world.mean_score(), 0.25);
+    }
+
+    #[test]
+    fn recordings_play_back_the_positions() {
+        let mut world: World<StdRng, AndiN> =
+            WorldBuilder::new().creatures(5).size(16, 16).steps_in_generation(10).seed(3).build();
+        world.set_snapshot_positions(true);
+        world.start_recording();
+        let snapshots: Vec<_> = (0..10).map(|_| world.step_once()).collect();
+
+        let frame: Vec<_> = world.replay_frame(0, 3).iter().map(|pos| (pos.x, pos.y)).collect();
+        assert_eq!(frame, snapshots[3].positions);
+        assert_eq!(world.recorder().recording(0).map(|recording| recording.len()), Some(10));
+        assert!(world.replay_frame(0, 10).is_empty());
+    }
+
+    #[test]
+    fn builder_and_constructor_build_the_same_world() {
+        let rng = StdRng::seed_from_u64(4);
+        let mut legacy: World<StdRng, AndiN> = World::new(100, 8, 2, 100, 48, 32, rng);
+        let mut built: World<StdRng, AndiN> = WorldBuilder::new()
+            .creatures(100)
+            .genome_length(8)
+            .internal_neurons(2)
+            .mutation_coeff(100)
+            .size(48, 32)
+            .seed(4)
+            .build();
+
+        let state = |world: &World<StdRng, AndiN>| {
+            let positions: Vec<_> = world.creatures.positions.iter().map(|p| (p.x, p.y)).collect();
+            (population_checksum(world.creatures.genoms()), positions, world.board_size())
+        };
+        assert_eq!(state(&built), state(&legacy));
+        assert_eq!(built.creatures.n_internal_neurons, 2);
+        legacy.run_headless(2);
+        built.run_headless(2);
+        assert_eq!(state(&built), state(&legacy));
+    }
+
+    #[test]
+    fn positions_stay_on_a_non_square_board() {
+        let mut world: World<StdRng, AndiN> = WorldBuilder::new()
+            .creatures(2000)
+            .size(300, 100)
+            .steps_in_generation(5)
+            .seed(5)
+            .build();
+        for _ in 0..3 {
+            assert_eq!(world.board_size(), (300, 100));
+            assert!(world.creatures.positions.iter().all(|pos| pos.x < 300 && pos.y < 100));
+            assert!(world.creatures.positions.iter().any(|pos| pos.x >= 100));
+            world.simulate_until_endofgeneration();
+        }
+    }
+
+    #[test]
+    fn draw_skips_positions_off_the_board() {
+        let mut world: World<StdRng, AndiN> =
+            WorldBuilder::new().creatures(3).size(32, 32).seed(6).build();
+        world.set_draw_colors(Color32::GREEN, Color32::RED, None);
+        // (40, 5) would alias (8, 6) in a row-major buffer
+        world.creatures.positions[0] = Point2::new(40, 5);
+        world.creatures.positions[1] = Point2::new(-1, 0);
+        let on_board = (world.creatures.positions[2].x, world.creatures.positions[2].y);
+
+        let mut pixels = vec![Color32::WHITE; 32 * 32];
+        assert_eq!(world.draw(&mut pixels), 2);
+        let drawn: Vec<_> = (0..32 * 32)
+            .filter(|&i| pixels[i] != Color32::WHITE)
+            .map(|i| (i as i32 % 32, i as i32 / 32))
+            .collect();
+        assert_eq!(drawn, [on_board]);
+    }
+
+    #[test]
+    fn exported_stats_have_a_row_per_generation() {
+        let mut world = small_world(7);
+        let stats = world.run_headless(4);
+        let path = std::env::temp_dir().join(format!("brains-stats-{}.csv", std::process::id()));
+        world.export_stats_csv(&path).unwrap();
+        let csv = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        let mut lines = csv.lines();
+        assert_eq!(lines.next(), Some(CSV_HEADER));
+        let rows: Vec<Vec<f32>> = lines
+            .map(|line| line.split(',').map(|field| field.parse().unwrap()).collect())
+            .collect();
+        assert_eq!(rows.len(), 4);
+        for (generation, (row, stats)) in rows.iter().zip(&stats).enumerate() {
+            assert_eq!(row[0], generation as f32);
+            assert_eq!(row[1], stats.survivors as f32);
+            assert_eq!(row[3], stats.mean_score);
+        }
+    }
+
+    #[test]
+    fn the_heatmap_counts_the_survivors_in_the_zone() {
+        let mut world: World<StdRng, AndiN> = WorldBuilder::new()
+            .creatures(100)
+            .genome_length(8)
+            .size(32, 32)
+            .steps_in_generation(10)
+            .zone(Zone::CenterCircle(6))
+            .seed(3)
+            .build();
+        let stats = world.run_headless(1);
+
+        let heat = world.survivor_heat();
+        assert!(stats[0].survivors > 0);
+        assert_eq!(heat.iter().sum::<u32>() as usize, stats[0].survivors);
+        let (width, height) = world.board_size();
+        let hottest = (0..heat.len()).max_by_key(|&i| heat[i]).unwrap() as i32;
+        let hottest = Point2::new(hottest % width, hottest / width);
+        assert!(Zone::CenterCircle(6).contains(&hottest, width, height));
+
+        world.clear_heatmap();
+        assert!(world.survivor_heat().iter().all(|&heat| heat == 0));
+    }
+
+    #[test]
+    fn reset_restarts_from_a_fresh_population() {
+        let genoms = |world: &World<StdRng, AndiN>| -> Vec<_> {
+            world.creatures.genoms().iter().map(Genom::to_hex_string).collect()
+        };
+        let mut world = small_world(1);
+        world.run_headless(3);
+        let evolved = genoms(&world);
+
+        world.reset(9);
+        assert_eq!(world.generation(), 0);
+        assert_eq!(world.step(), 0);
+        assert!(world.history().is_empty());
+        assert_eq!(world.creatures.genoms().len(), 100);
+        let first = genoms(&world);
+        assert_ne!(first, evolved);
+
+        world.run_headless(2);
+        world.reset(9);
+        assert_eq!(genoms(&world), first);
+    }
+
+    #[test]
+    fn the_history_plots_are_drawn_headless() {
+        let mut world = small_world(2);
+        let mut ctx = egui::CtxRef::default();
+        let mut draw = |world: &World<StdRng, AndiN>| {
+            ctx.begin_frame(egui::RawInput::default());
+            egui::CentralPanel::default().show(&ctx, |ui| world.history_ui(ui));
+            let (_, shapes) = ctx.end_frame();
+            shapes.len()
+        };
+
+        let placeholder = draw(&world);
+        world.run_headless(3);
+        assert!(draw(&world) > placeholder);
+    }
+
+    #[test]
+    fn a_300_by_200_world_draws_into_a_300_by_200_buffer() {
+        let (width, height) = (300, 200);
+        let mut world: World<StdRng, AndiN> =
+            WorldBuilder::new().creatures(50).size(width, height).seed(8).build();
+        world.set_draw_colors(Color32::GREEN, Color32::RED, None);
+        let mut expected: Vec<_> = world.creatures.positions.iter().map(|p| (p.x, p.y)).collect();
+        expected.sort_unstable_by_key(|&(x, y)| (y, x));
+
+        let mut pixels = vec![Color32::WHITE; (width * height) as usize];
+        // nothing is skipped
+        assert_eq!(world.draw(&mut pixels), 0);
+        let drawn: Vec<_> = (0..pixels.len() as i32)
+            .filter(|&i| pixels[i as usize] != Color32::WHITE)
+            .map(|i| (i % width, i / width))
+            .collect();
+        assert_eq!(drawn, expected);
+
+        // the heatmap has the same layout
+        world.run_headless(1);
+        let mut heat = vec![Color32::WHITE; (width * height) as usize];
+        world.draw_heatmap(&mut heat);
+        for (i, &pixel) in heat.iter().enumerate() {
+            assert_eq!(pixel == Color32::BLACK, world.survivor_heat()[i] == 0);
+        }
+    }
+
+    #[test]
+    fn step_snapshots_cross_the_generation_boundary() {
+        let mut world = small_world(5);
+        let snapshots: Vec<_> = (0..25).map(|_| world.step_once()).collect();
+
+        let progress: Vec<_> = snapshots.iter().map(|s| (s.generation, s.step)).collect();
+        let expected: Vec<_> = (1..=25).map(|n| (n / 10, n % 10)).collect();
+        assert_eq!(progress, expected);
+        let ended: Vec<_> = (0..25).filter(|&i| snapshots[i].generation_ended).collect();
+        assert_eq!(ended, [9, 19]);
+        assert!(snapshots.iter().all(|s| s.n_creatures == 100 && s.positions.is_empty()));
+
+        world.set_snapshot_positions(true);
+        let snapshot = world.step_once();
+        let positions: Vec<_> = world.creatures.positions.iter().map(|p| (p.x, p.y)).collect();
+        assert_eq!(snapshot.positions, positions);
+    }
+
+    #[test]
+    fn centroid_and_spread_of_symmetric_creatures() {
+        let mut world: World<StdRng, AndiN> =
+            WorldBuilder::new().creatures(4).size(32, 32).seed(9).build();
+        // the corners of a 6 x 8 rectangle, each 5 cells from its center
+        let corners = [(9, 8), (15, 8), (9, 16), (15, 16)];
+        world.creatures.positions = corners.iter().map(|&(x, y)| Point2::new(x, y)).collect();
+
+        assert_eq!(world.centroid(), (12.0, 12.0));
+        assert_eq!(world.spread(), 5.0);
+
+        world.creatures.positions.clear();
+        assert_eq!(world.centroid(), (0.0, 0.0));
+        assert_eq!(world.spread(), 0.0);
+    }
+
+    #[test]
+    fn reseeding_refills_the_population_when_nobody_survives() {
+        let mut world = small_world(4);
+        // the zone only scores the creatures, it's the food requirement nobody can meet
+        world.set_food_threshold(Some(u32::MAX));
+        world.set_extinction_policy(ExtinctionPolicy::Reseed);
+        let before: Vec<_> = world.creatures.genoms().iter().map(Genom::to_hex_string).collect();
+
+        world.run_headless(1);
+        assert_eq!(world.creatures.genoms().len(), 100);
+        assert_eq!(world.creatures.positions.len(), 100);
+        // a fresh random population, not bred from the old one
+        let after = world.creatures.genoms();
+        assert!(after.iter().all(|genom| !before.contains(&genom.to_hex_string())));
+    }
+
+    #[test]
+    #[should_panic(expected = "died out")]
+    fn extinction_stops_the_simulation_under_the_panic_policy() {
+        let mut world = small_world(4);
+        world.set_food_threshold(Some(u32::MAX));
+        world.set_extinction_policy(ExtinctionPolicy::Panic);
+        world.run_headless(1);
+    }
+
+    #[test]
+    fn advance_generations_reports_each_generation_and_stops_when_asked() {
+        let mut world = small_world(6);
+        let mut reports = Vec::new();
+        let completed = world.advance_generations(10, |report| {
+            reports.push((report.generation, report.completed));
+            true
+        });
+        assert_eq!(completed, 10);
+        assert_eq!(reports, (0..10).map(|g| (g, g + 1)).collect::<Vec<_>>());
+
+        let mut world = small_world(6);
+        let completed = world.advance_generations(10, |report| report.completed < 5);
+        assert_eq!(completed, 5);
+        assert_eq!(world.generation(), 5);
+    }
+
+    #[test]
+    fn snapshots_keep_the_positions_of_their_step() {
+        let coordinates = |positions: &[Point2]| -> Vec<_> {
+            positions.iter().map(|pos| (pos.x, pos.y)).collect()
+        };
+        let mut world = small_world(2);
+        for _ in 0..4 {
+            world.step_once();
+        }
+        let snapshot = world.snapshot();
+        let at_step_4 = coordinates(&world.creatures.positions);
+        assert_eq!((snapshot.generation, snapshot.step), (0, 4));
+        assert_eq!(coordinates(&snapshot.positions), at_step_4);
+
+        for _ in 0..3 {
+            world.step_once();
+        }
+        assert_ne!(coordinates(&world.creatures.positions), at_step_4);
+        // the snapshot is drawn on another thread while the world goes on
+        let drawn = std::thread::spawn(move || {
+            let mut pixels = vec![Color32::WHITE; 32 * 32];
+            snapshot.draw(&mut pixels);
+            let mut drawn: Vec<_> = (0..32 * 32)
+                .filter(|&i| pixels[i] != Color32::WHITE)
+                .map(|i| (i as i32 % 32, i as i32 / 32))
+                .collect();
+            drawn.sort_unstable();
+            drawn
+        });
+        let mut expected = at_step_4;
+        expected.sort_unstable();
+        assert_eq!(drawn.join().unwrap(), expected);
+    }
+
+    #[test]
+    fn a_generation_times_each_of_its_phases() {
+        let mut world = small_world(13);
+        let timings = world.last_timings();
+        assert_eq!(timings.decide + timings.apply + timings.end_generation, Duration::ZERO);
+
+        world.run_headless(1);
+        let timings = world.last_timings();
+        assert!(timings.decide > Duration::ZERO, "{:?}", timings);
+        assert!(timings.apply > Duration::ZERO, "{:?}", timings);
+        assert!(timings.end_generation > Duration::ZERO, "{:?}", timings);
+    }
+
+    #[test]
+    fn creatures_evolve_towards_a_center_circle() {
+        const STEPS: u32 = 30;
+        let zone = Zone::CenterCircle(4);
+        let mut world: World<StdRng, AndiN> = WorldBuilder::new()
+            .creatures(100)
+            .genome_length(8)
+            .size(32, 32)
+            .steps_in_generation(STEPS)
+            .zone(zone.clone())
+            .seed(8)
+            .build();
+
+        // the mean distance to the circle at the last step of each generation, right before the
+        // creatures are respawned
+        let distances: Vec<f32> = (0..50)
+            .map(|_| {
+                for _ in 1..STEPS {
+                    world.simulate();
+                }
+                let positions = &world.creatures.positions;
+                let sum: f32 = positions.iter().map(|pos| zone.distance(pos, 32, 32)).sum();
+                let mean = sum / positions.len() as f32;
+                world.simulate();
+                mean
+            })
+            .collect();
+        assert_eq!(world.generation(), 50);
+
+        let mean = |distances: &[f32]| distances.iter().sum::<f32>() / distances.len() as f32;
+        assert!(mean(&distances[45..]) < mean(&distances[..5]), "distances: {:?}", distances);
+    }
 }
@@ -0,0 +1,209 @@
+use crate::creature::Point2;
+#[cfg(feature = "serde")]
+use serde::Deserialize;
+
+// -------------------------------------------------------------------------------------------------
+// --- Selection Zones -----------------------------------------------------------------------------
+// -------------------------------------------------------------------------------------------------
+
+/// A selection zone decides whether a creature survives the generation, based on where it ended
+/// up on the board.
+///
+/// Any closure `Fn(&Point2, i32, i32) -> bool` can be used as a zone, for everything else there
+/// are the built-in [`Zone`]s.
+pub trait SelectionZone {
+    /// returns `true` if a creature at `pos` survives on a board of the given dimensions
+    fn contains(&self, pos: &Point2, width: i32, height: i32) -> bool;
+
+    /// distance (in cells) from `pos` to the zone, `0.0` inside of it.
+    /// The default just tells inside from outside.
+    fn distance(&self, pos: &Point2, width: i32, height: i32) -> f32 {
+        if self.contains(pos, width, height) {
+            0.0
+        } else {
+            1.0
+        }
+    }
+}
+
+impl<F> SelectionZone for F
+where
+    F: Fn(&Point2, i32, i32) -> bool,
+{
+    fn contains(&self, pos: &Point2, width: i32, height: i32) -> bool {
+        self(pos, width, height)
+    }
+}
+
+/// The built-in selection zones.
+///
+/// Zones can be combined into expression trees with `And`, `Or` and `Not`, e.g.
+/// `Zone::Rect { x: 0, y: 0, width: 10, height: 10 }.or(Zone::Circle { x: 50, y: 50, radius: 5 })`.
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(Deserialize))]
+pub enum Zone {
+    /// the right half of the board (this was the original hardcoded rule)
+    #[default]
+    RightHalf,
+    /// the left half of the board
+    LeftHalf,
+    /// a circle around the board center, radius given in cells
+    CenterCircle(i32),
+    /// four squares in the corners of the board, side length given in cells
+    Corners(i32),
+    /// an axis aligned rectangle, `(x, y)` being its top left corner
+    Rect { x: i32, y: i32, width: i32, height: i32 },
+    /// a circle around `(x, y)`
+    Circle { x: i32, y: i32, radius: i32 },
+    /// inside of both zones
+    And(Box<Zone>, Box<Zone>),
+    /// inside of at least one of the zones
+    Or(Box<Zone>, Box<Zone>),
+    /// outside of the zone
+    Not(Box<Zone>),
+}
+
+impl Zone {
+    pub fn and(self, other: Zone) -> Zone {
+        Zone::And(Box::new(self), Box::new(other))
+    }
+
+    pub fn or(self, other: Zone) -> Zone {
+        Zone::Or(Box::new(self), Box::new(other))
+    }
+}
+
+impl std::ops::Not for Zone {
+    type Output = Zone;
+
+    fn not(self) -> Zone {
+        Zone::Not(Box::new(self))
+    }
+}
+
+impl SelectionZone for Zone {
+    fn contains(&self, pos: &Point2, width: i32, height: i32) -> bool {
+        match *self {
+            Zone::RightHalf => pos.x >= width / 2,
+            Zone::LeftHalf => pos.x < width / 2,
+            Zone::CenterCircle(radius) => {
+                let dx = pos.x - width / 2;
+                let dy = pos.y - height / 2;
+                dx * dx + dy * dy <= radius * radius
+            }
+            Zone::Corners(size) => {
+                (pos.x < size || pos.x >= width - size) && (pos.y < size || pos.y >= height - size)
+            }
+            Zone::Rect {
+                x,
+                y,
+                width: w,
+                height: h,
+            } => pos.x >= x && pos.x < x + w && pos.y >= y && pos.y < y + h,
+            Zone::Circle { x, y, radius } => {
+                let dx = pos.x - x;
+                let dy = pos.y - y;
+                dx * dx + dy * dy <= radius * radius
+            }
+            Zone::And(ref a, ref b) => {
+                a.contains(pos, width, height) && b.contains(pos, width, height)
+            }
+            Zone::Or(ref a, ref b) => {
+                a.contains(pos, width, height) || b.contains(pos, width, height)
+            }
+            Zone::Not(ref zone) => !zone.contains(pos, width, height),
+        }
+    }
+
+    fn distance(&self, pos: &Point2, width: i32, height: i32) -> f32 {
+        match *self {
+            Zone::RightHalf => (width / 2 - pos.x).max(0) as f32,
+            Zone::LeftHalf => (pos.x - (width / 2 - 1)).max(0) as f32,
+            Zone::CenterCircle(radius) => {
+                let dx = (pos.x - width / 2) as f32;
+                let dy = (pos.y - height / 2) as f32;
+                ((dx * dx + dy * dy).sqrt() - radius as f32).max(0.0)
+            }
+            Zone::Corners(size) => {
+                // distance to the nearest band along each axis, the corners are where they cross
+                let band = |x: i32, len: i32| {
+                    if x < size || x >= len - size {
+                        0.0
+                    } else {
+                        (x - (size - 1)).min(len - size - x) as f32
+                    }
+                };
+                let dx = band(pos.x, width);
+                let dy = band(pos.y, height);
+                (dx * dx + dy * dy).sqrt()
+            }
+            Zone::Rect {
+                x,
+                y,
+                width: w,
+                height: h,
+            } => {
+                let dx = (x - pos.x).max(pos.x - (x + w - 1)).max(0) as f32;
+                let dy = (y - pos.y).max(pos.y - (y + h - 1)).max(0) as f32;
+                (dx * dx + dy * dy).sqrt()
+            }
+            Zone::Circle { x, y, radius } => {
+                let dx = (pos.x - x) as f32;
+                let dy = (pos.y - y) as f32;
+                ((dx * dx + dy * dy).sqrt() - radius as f32).max(0.0)
+            }
+            // the larger distance is a lower bound of the distance to the intersection
+            Zone::And(ref a, ref b) => {
+                if self.contains(pos, width, height) {
+                    0.0
+                } else {
+                    a.distance(pos, width, height).max(b.distance(pos, width, height))
+                }
+            }
+            Zone::Or(ref a, ref b) => {
+                a.distance(pos, width, height).min(b.distance(pos, width, height))
+            }
+            // the distance to the complement isn't tracked, just tell inside from outside
+            Zone::Not(ref zone) => {
+                if zone.contains(pos, width, height) {
+                    1.0
+                } else {
+                    0.0
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn either_quarter_of_an_or_zone_survives() {
+        let (width, height) = (100, 20);
+        let left_quarter = Zone::Rect {
+            x: 0,
+            y: 0,
+            width: width / 4,
+            height,
+        };
+        let right_quarter = Zone::Rect {
+            x: width - width / 4,
+            y: 0,
+            width: width / 4,
+            height,
+        };
+        let zone = left_quarter.or(right_quarter);
+
+        let survives = |x: i32| zone.contains(&Point2::new(x, 10), width, height);
+        assert!(survives(0));
+        assert!(survives(24));
+        assert!(survives(75));
+        assert!(survives(99));
+        assert!(!survives(25));
+        assert!(!survives(50));
+        assert!(!survives(74));
+        assert_eq!(zone.distance(&Point2::new(50, 10), width, height), 25.0);
+    }
+}